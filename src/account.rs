@@ -0,0 +1,80 @@
+use crate::{api::AocClient, credentials::account_key, defines::app_config_dir, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// AOC accounts that session tokens and profiles belong to.
+///
+/// Caches and histories are keyed by the account rather than by the token, so that they survive
+/// AOC rotating the session cookie.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Accounts {
+    /// Account by [`account_key`] of a session token
+    tokens: BTreeMap<String, String>,
+    /// Account of the last session token used by each profile
+    profiles: BTreeMap<String, String>,
+}
+
+impl Accounts {
+    fn path() -> PathBuf {
+        app_config_dir().unwrap_or_default().join("accounts.ron")
+    }
+    pub fn load() -> Result<Self> {
+        confy::load_path(Self::path()).map_err(Into::into)
+    }
+    pub fn store(&self) -> Result<()> {
+        confy::store_path(Self::path(), self).map_err(Into::into)
+    }
+    /// Account of the session token, if it was resolved before
+    pub fn for_token(&self, session_token: &str) -> Option<&str> {
+        Some(self.tokens.get(&account_key(session_token))?)
+    }
+    /// Account the profile last used, useful when no session token is available
+    pub fn for_profile(&self, profile: &str) -> Option<&str> {
+        Some(self.profiles.get(profile)?)
+    }
+    /// Account the session token belongs to, asking AOC for it only the first time the token is
+    /// seen.
+    ///
+    /// This is best-effort, so that a mirror or proxy without a settings page still works: if AOC
+    /// can't be asked or the mapping can't be stored, the token's [`account_key`] is used instead.
+    pub fn resolve(profile: &str, session_token: &str, base_url: &str) -> String {
+        let mut accounts = Self::load().unwrap_or_default();
+        let mut changed = false;
+        let account = match accounts.for_token(session_token) {
+            Some(account) => account.to_string(),
+            None => match Self::lookup(session_token, base_url) {
+                Some(account) => {
+                    accounts
+                        .tokens
+                        .insert(account_key(session_token), account.clone());
+                    changed = true;
+                    account
+                }
+                // not remembered, so that the lookup is tried again next time
+                None => account_key(session_token),
+            },
+        };
+        if accounts.for_profile(profile) != Some(&account) {
+            accounts
+                .profiles
+                .insert(profile.to_string(), account.clone());
+            changed = true;
+        }
+        if changed {
+            let _ = accounts.store();
+        }
+        account
+    }
+    /// AOC user id of the session token, from its settings page
+    fn lookup(session_token: &str, base_url: &str) -> Option<String> {
+        let user = AocClient::builder()
+            .base_url(base_url)
+            .session_token(session_token)
+            .retries(0)
+            .build()
+            .user()
+            .ok()?;
+        Some(user.id?.to_string())
+    }
+}
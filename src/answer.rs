@@ -0,0 +1,229 @@
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::{fmt, time::Duration};
+
+/// Verdict for a submitted answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without any hint
+    Wrong,
+    /// Part was already solved, or isn't unlocked yet
+    AlreadySolved,
+    /// An answer was submitted too recently, along with the wait time left if AOC told us
+    RateLimited(Option<Duration>),
+}
+
+/// Response to a submitted answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    pub verdict: Verdict,
+    /// Time to wait before another answer can be submitted, if any
+    pub cooldown: Option<Duration>,
+}
+
+impl Outcome {
+    /// Parses the page AOC responds with after a submission
+    pub fn parse(html: &str) -> Option<Self> {
+        let selector = Selector::parse("main article").unwrap();
+        let text: String = Html::parse_document(html)
+            .select(&selector)
+            .next()?
+            .text()
+            .collect();
+
+        let verdict = Verdict::parse(&text)?;
+        let cooldown = match verdict {
+            Verdict::RateLimited(wait) => wait,
+            // wrong answers come with "Please wait one minute before trying again."
+            _ => parse_wrong_wait(&text),
+        };
+        Some(Self { verdict, cooldown })
+    }
+}
+
+impl Verdict {
+    /// Parses the verdict out of the text of the response article
+    fn parse(text: &str) -> Option<Self> {
+        if text.contains("That's the right answer") {
+            Some(Verdict::Correct)
+        } else if text.contains("You don't seem to be solving the right level") {
+            Some(Verdict::AlreadySolved)
+        } else if text.contains("You gave an answer too recently") {
+            Some(Verdict::RateLimited(parse_wait(text)))
+        } else if text.contains("your answer is too high") {
+            Some(Verdict::TooHigh)
+        } else if text.contains("your answer is too low") {
+            Some(Verdict::TooLow)
+        } else if text.contains("That's not the right answer") {
+            Some(Verdict::Wrong)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "That's the right answer!"),
+            Verdict::TooHigh => write!(f, "That's not the right answer; your answer is too high"),
+            Verdict::TooLow => write!(f, "That's not the right answer; your answer is too low"),
+            Verdict::Wrong => write!(f, "That's not the right answer"),
+            Verdict::AlreadySolved => write!(
+                f,
+                "You don't seem to be solving the right level, did you already complete it?"
+            ),
+            Verdict::RateLimited(Some(wait)) => write!(
+                f,
+                "You gave an answer too recently; you have {}s left to wait",
+                wait.as_secs()
+            ),
+            Verdict::RateLimited(None) => write!(f, "You gave an answer too recently"),
+        }
+    }
+}
+
+/// Parses durations like "You have 1m 5s left to wait"
+fn parse_wait(text: &str) -> Option<Duration> {
+    let (_, rest) = text.split_once("You have ")?;
+    let (wait, _) = rest.split_once(" left to wait")?;
+    let mut secs = 0;
+    for part in wait.split_whitespace() {
+        let (value, unit) = part.split_at(part.find(|ch: char| !ch.is_ascii_digit())?);
+        let value: u64 = value.parse().ok()?;
+        secs += match unit {
+            "h" => value * 3600,
+            "m" => value * 60,
+            "s" => value,
+            _ => return None,
+        };
+    }
+    Some(Duration::from_secs(secs))
+}
+
+/// Parses durations like "Please wait 5 minutes before trying again"
+fn parse_wrong_wait(text: &str) -> Option<Duration> {
+    let (_, rest) = text.split_once("lease wait ")?;
+    let (wait, _) = rest.split_once(" before trying again")?;
+    let (value, unit) = wait.split_once(' ')?;
+    let value = match value {
+        "one" => 1,
+        value => value.parse().ok()?,
+    };
+    match unit.trim_end_matches('s') {
+        "second" => Some(Duration::from_secs(value)),
+        "minute" => Some(Duration::from_secs(value * 60)),
+        "hour" => Some(Duration::from_secs(value * 3600)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(text: &str) -> String {
+        format!(
+            "<html><body><main><article><p>{}</p></article></main></body></html>",
+            text
+        )
+    }
+
+    #[test]
+    fn parses_verdicts() {
+        let cases = [
+            (
+                "That's the right answer! You are one gold star closer.",
+                Verdict::Correct,
+            ),
+            (
+                "That's not the right answer; your answer is too high.",
+                Verdict::TooHigh,
+            ),
+            (
+                "That's not the right answer; your answer is too low.",
+                Verdict::TooLow,
+            ),
+            (
+                "That's not the right answer. If you're stuck, ...",
+                Verdict::Wrong,
+            ),
+            (
+                "You don't seem to be solving the right level. Did you already complete it?",
+                Verdict::AlreadySolved,
+            ),
+            (
+                "You gave an answer too recently; you have to wait after submitting an answer.",
+                Verdict::RateLimited(None),
+            ),
+        ];
+        for (text, verdict) in cases {
+            assert_eq!(Verdict::parse(text), Some(verdict), "{}", text);
+        }
+        assert_eq!(Verdict::parse("Something else entirely"), None);
+    }
+
+    #[test]
+    fn parses_rate_limit_wait() {
+        let text = "You gave an answer too recently; you have to wait after submitting an answer \
+                    before trying again. You have 1m 5s left to wait.";
+        assert_eq!(
+            Verdict::parse(text),
+            Some(Verdict::RateLimited(Some(Duration::from_secs(65))))
+        );
+        assert_eq!(
+            parse_wait("You have 30s left to wait."),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_wait("You have 1h 2m 3s left to wait."),
+            Some(Duration::from_secs(3723))
+        );
+        assert_eq!(parse_wait("You have a while left to wait."), None);
+    }
+
+    #[test]
+    fn parses_wrong_answer_wait() {
+        assert_eq!(
+            parse_wrong_wait("Please wait one minute before trying again."),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            parse_wrong_wait("please wait 5 minutes before trying again."),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(
+            parse_wrong_wait("Please wait a bit before trying again."),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_outcome() {
+        let html = page(
+            "That's not the right answer; your answer is too low. \
+             Please wait one minute before trying again.",
+        );
+        assert_eq!(
+            Outcome::parse(&html),
+            Some(Outcome {
+                verdict: Verdict::TooLow,
+                cooldown: Some(Duration::from_secs(60)),
+            })
+        );
+
+        let html = page("You gave an answer too recently. You have 45s left to wait.");
+        assert_eq!(
+            Outcome::parse(&html),
+            Some(Outcome {
+                verdict: Verdict::RateLimited(Some(Duration::from_secs(45))),
+                cooldown: Some(Duration::from_secs(45)),
+            })
+        );
+
+        assert_eq!(Outcome::parse("<html><body></body></html>"), None);
+    }
+}
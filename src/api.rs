@@ -1,31 +1,319 @@
 use crate::{
-    defines::{API_HEADER_FROM, API_HEADER_USER_AGENT},
-    inputs::AdvInput,
+    answer::Outcome,
+    cache::Cache,
+    defines::{
+        API_HEADER_FROM, API_HEADER_USER_AGENT, DEFAULT_BACKOFF, DEFAULT_BASE_URL,
+        DEFAULT_MIN_DELAY, DEFAULT_RETRIES, DEFAULT_WORKERS, MAX_BACKOFF,
+    },
+    problem::Problem,
+    user::User,
+    Error, Result,
 };
-use std::time::Duration;
-use ureq::{AgentBuilder, Response};
-
-/// Fetches AOC inputs synchronously
-pub fn fetch_inputs(
-    inputs: &Vec<AdvInput>,
-    session_token: &str,
-) -> Vec<Result<Response, ureq::Error>> {
-    let mut out = vec![];
-    let agent = AgentBuilder::new()
-        .timeout_read(Duration::from_secs(5))
-        .timeout_write(Duration::from_secs(5))
-        .build();
-    let session_token = format!("session={}", session_token);
-
-    for input in inputs {
-        let body = agent
-            .get(&input.request_url())
-            .set("Cookie", &session_token)
-            .set(API_HEADER_USER_AGENT[0], API_HEADER_USER_AGENT[1])
-            .set(API_HEADER_FROM[0], API_HEADER_FROM[1])
-            .call();
-        out.push(body);
-    }
-
-    out
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use ureq::{Agent, AgentBuilder, Request, Response};
+
+/// State of a single input in [`AocClient::inputs`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchState {
+    Fetching,
+    /// Waiting before the given retry attempt, after a transient failure
+    Retrying(u32),
+    Done,
+    Failed,
+}
+
+/// Keeps a minimum delay between the start of any two requests, across all threads
+#[derive(Debug)]
+struct Throttle {
+    min_delay: Duration,
+    last: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    fn wait(&self) {
+        let mut last = self.last.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(elapsed) = last.map(|last| last.elapsed()) {
+            if elapsed < self.min_delay {
+                thread::sleep(self.min_delay - elapsed);
+            }
+        }
+        *last = Some(Instant::now());
+    }
+}
+
+/// Reusable AOC client, owning the HTTP agent, session cookie and headers
+#[derive(Debug, Clone)]
+pub struct AocClient {
+    agent: Agent,
+    base_url: String,
+    session_cookie: Option<String>,
+    headers: Vec<(String, String)>,
+    workers: usize,
+    throttle: Arc<Throttle>,
+    retries: u32,
+    backoff: Duration,
+    cache: Option<Cache>,
+    refresh: bool,
+    offline: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct AocClientBuilder {
+    session_token: Option<String>,
+    base_url: String,
+    timeout: Duration,
+    headers: Vec<(String, String)>,
+    workers: usize,
+    min_delay: Duration,
+    retries: u32,
+    backoff: Duration,
+    cache: Option<Cache>,
+    refresh: bool,
+    offline: bool,
+}
+
+impl Default for AocClientBuilder {
+    fn default() -> Self {
+        Self {
+            session_token: None,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            timeout: Duration::from_secs(5),
+            headers: [API_HEADER_USER_AGENT, API_HEADER_FROM]
+                .iter()
+                .map(|[name, value]| (name.to_string(), value.to_string()))
+                .collect(),
+            workers: DEFAULT_WORKERS,
+            min_delay: DEFAULT_MIN_DELAY,
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            cache: None,
+            refresh: false,
+            offline: false,
+        }
+    }
+}
+
+impl AocClientBuilder {
+    pub fn session_token(mut self, token: impl Into<String>) -> Self {
+        self.session_token = Some(token.into());
+        self
+    }
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        self
+    }
+    /// Read and write timeout for every request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    /// Adds a header to every request, replacing any previous header with the same name
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(&name));
+        self.headers.push((name, value.into()));
+        self
+    }
+    /// Number of concurrent requests made by [`AocClient::inputs`]
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+    /// Minimum delay between any two requests, to stay within AOC's automation guidelines
+    pub fn min_delay(mut self, delay: Duration) -> Self {
+        self.min_delay = delay;
+        self
+    }
+    /// Number of retries for transient failures, see [`crate::Error::is_transient`]
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+    /// Base delay for the exponential backoff between retries
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+    /// Serve inputs from the cache when available, and store fetched inputs in it
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+    /// Fetch inputs even if they're cached, updating the cache, unless offline
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+    /// Only serve inputs from the cache, never touching the network
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+    pub fn build(self) -> AocClient {
+        AocClient {
+            agent: AgentBuilder::new()
+                .timeout_read(self.timeout)
+                .timeout_write(self.timeout)
+                .build(),
+            base_url: self.base_url.trim_end_matches('/').to_string(),
+            session_cookie: self.session_token.map(|token| format!("session={}", token)),
+            headers: self.headers,
+            workers: self.workers,
+            throttle: Arc::new(Throttle {
+                min_delay: self.min_delay,
+                last: Mutex::new(None),
+            }),
+            retries: self.retries,
+            backoff: self.backoff,
+            cache: self.cache,
+            refresh: self.refresh,
+            offline: self.offline,
+        }
+    }
+}
+
+impl AocClient {
+    pub fn builder() -> AocClientBuilder {
+        AocClientBuilder::default()
+    }
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+    /// Fetches the puzzle input of the given day
+    pub fn input(&self, year: i32, day: u32) -> Result<String> {
+        self.retry(|| self.fetch_input(year, day), |_| {})
+    }
+    /// Fetches the puzzle statement of the given day
+    pub fn problem(&self, year: i32, day: u32) -> Result<Problem> {
+        let html = self.retry(
+            || Ok(self.get(&format!("/{}/day/{}", year, day))?.into_string()?),
+            |_| {},
+        )?;
+        Ok(Problem::parse(&html, &self.base_url))
+    }
+    /// Fetches the account the session token belongs to, erroring with [`Error::InvalidSession`]
+    /// if it's invalid or expired
+    pub fn user(&self) -> Result<User> {
+        let html = self
+            .retry(|| Ok(self.get("/settings")?.into_string()?), |_| {})
+            .map_err(|err| match err {
+                // there's no puzzle to be locked here, the page is just missing
+                Error::NotUnlocked => Error::Status(404),
+                err => err,
+            })?;
+        User::parse(&html).ok_or(Error::InvalidSession)
+    }
+    /// Submits an answer for the given part, which is never retried since it's not idempotent
+    pub fn submit(&self, year: i32, day: u32, part: u8, answer: &str) -> Result<Outcome> {
+        self.throttle.wait();
+        let html = self
+            .request("POST", &format!("/{}/day/{}/answer", year, day))
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])?
+            .into_string()?;
+        Outcome::parse(&html).ok_or(Error::UnexpectedResponse)
+    }
+    /// Fetches multiple `(year, day)` inputs concurrently, returning the results in the same order.
+    ///
+    /// `on_state` gets called with the index of the input whenever its state changes.
+    pub fn inputs<F>(&self, days: &[(i32, u32)], on_state: F) -> Vec<Result<String>>
+    where
+        F: Fn(usize, FetchState) + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let mut out: Vec<_> = thread::scope(|s| {
+            let handles: Vec<_> = (0..self.workers.min(days.len()))
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut out = vec![];
+                        loop {
+                            let idx = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(&(year, day)) = days.get(idx) else {
+                                break out;
+                            };
+                            on_state(idx, FetchState::Fetching);
+                            let resp = self.retry(
+                                || self.fetch_input(year, day),
+                                |attempt| on_state(idx, FetchState::Retrying(attempt)),
+                            );
+                            on_state(
+                                idx,
+                                match resp {
+                                    Ok(_) => FetchState::Done,
+                                    Err(_) => FetchState::Failed,
+                                },
+                            );
+                            out.push((idx, resp));
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("fetch worker panicked"))
+                .collect()
+        });
+        out.sort_by_key(|(idx, _)| *idx);
+        out.into_iter().map(|(_, resp)| resp).collect()
+    }
+    fn fetch_input(&self, year: i32, day: u32) -> Result<String> {
+        let cached = match self.refresh && !self.offline {
+            true => None,
+            false => self.cache.as_ref().and_then(|cache| cache.get(year, day)),
+        };
+        if let Some(input) = cached {
+            return Ok(input);
+        }
+        if self.offline {
+            return Err(Error::NotCached);
+        }
+        let input = self
+            .get(&format!("/{}/day/{}/input", year, day))?
+            .into_string()?;
+        if let Some(cache) = &self.cache {
+            cache.put(year, day, &input)?;
+        }
+        Ok(input)
+    }
+    /// Retries `f` on transient failures, with exponential backoff capped at [`MAX_BACKOFF`] and
+    /// jitter
+    fn retry<T>(&self, mut f: impl FnMut() -> Result<T>, on_retry: impl Fn(u32)) -> Result<T> {
+        let mut attempt = 0;
+        loop {
+            match f() {
+                Err(err) if err.is_transient() && attempt < self.retries => {
+                    attempt += 1;
+                    on_retry(attempt);
+                    let delay = self
+                        .backoff
+                        .checked_mul(2u32.saturating_pow(attempt - 1))
+                        .map_or(MAX_BACKOFF, |delay| delay.min(MAX_BACKOFF));
+                    thread::sleep(delay / 2 + delay.mul_f64(fastrand::f64() / 2.0));
+                }
+                resp => break resp,
+            }
+        }
+    }
+    fn request(&self, method: &str, path: &str) -> Request {
+        let mut req = self
+            .agent
+            .request(method, &format!("{}{}", self.base_url, path));
+        if let Some(cookie) = &self.session_cookie {
+            req = req.set("Cookie", cookie);
+        }
+        for (name, value) in &self.headers {
+            req = req.set(name, value);
+        }
+        req
+    }
+    fn get(&self, path: &str) -> Result<Response> {
+        self.throttle.wait();
+        Ok(self.request("GET", path).call()?)
+    }
 }
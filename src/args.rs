@@ -1,69 +1,260 @@
+use crate::{browser::Browser, range::RangeList};
 use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None, arg_required_else_help = true)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Base URL for AOC requests [default: https://adventofcode.com]
+    #[arg(long, global = true, value_name = "URL")]
+    pub base_url: Option<String>,
+    /// Read the session token from a file, taking precedence over `YAADV_SESSION` and the stored token
+    #[arg(long, global = true, value_name = "PATH")]
+    pub token_file: Option<PathBuf>,
+    /// Credentials profile to use [default: the active profile]
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
-    // ! Not certain if I want to add this feat...though, If I did, I'd also have to add a feat for submiting answers aswell.
-    // /// Fetch problem statement from AOC
-    // // Prints to stdout
-    // #[command(short_flag = 'p')]
-    // Problem(Problem),
+    /// Fetch problem statement from AOC
+    #[command(short_flag = 'P')]
+    Problem(Problem),
+    /// Extract example inputs and their expected answers from the problem statement
+    #[command(short_flag = 'E')]
+    Examples(Examples),
+    /// Submit an answer to AOC
+    #[command(
+        short_flag = 'S',
+        after_help = r#"Exit codes: 0 correct, 10 wrong, 11 too high, 12 too low, 13 already solved, 14 rate limited,
+15 refused locally since the answer is already known to be wrong"#
+    )]
+    Submit(Submit),
     /// Fetch your AOC inputs
     #[command(short_flag = 'I')]
     Inputs(Inputs),
-    /// Manage your AOC session token; enters interactive mode by default
+    /// Manage your AOC session tokens; enters interactive mode by default
     #[command(
         short_flag = 'C',
         after_help = r#"To learn how to get your session token, take a look at:
 https://github.com/nozwock/yaadv#setting-up-the-cli"#
     )]
     Credentials(Credentials),
+    /// Inspect the effective configuration
+    #[command(
+        after_help = r#"Config is layered, later layers taking precedence: built-in defaults, the global config,
+the project config, `YAADV_*` env variables, then CLI flags.
+
+The project config is the closest .yaadv.ron, .yaadv, .yaadv.toml, yaadv.json, or Cargo.toml
+with a [package.metadata.yaadv] table, in pwd or any parent folder up to the git root"#
+    )]
+    Config(ConfigArgs),
 }
 
-// #[derive(Args, Debug)]
-// pub struct Problem {
-//     #[arg(short, long, required = true)]
-//     pub day: Option<u32>,
-//     /// Current AOC year by default
-//     #[arg(short, long)]
-//     pub year: Option<i32>,
-// }
+#[derive(Args, Debug)]
+pub struct Problem {
+    #[arg(short, long)]
+    pub day: u32,
+    /// Current AOC year by default
+    #[arg(short, long)]
+    pub year: Option<i32>,
+    /// Print as Markdown instead of formatted text
+    #[arg(short, long)]
+    pub markdown: bool,
+    /// Save as Markdown instead of printing, in `./puzzles/{{year}}/day{{day}}/README.md` by default
+    #[arg(short, long)]
+    pub save: bool,
+    /// Set formatted output path for the saved problem, see `yaadv -I --help`
+    #[arg(short = 'o', long, value_name = "PATTERN", requires = "save")]
+    pub formatted_path: Option<String>,
+}
 
 #[derive(Args, Debug)]
-pub struct Inputs {
-    /// All 25 days by default
+pub struct Examples {
     #[arg(short, long)]
-    pub day: Option<u32>,
+    pub day: u32,
     /// Current AOC year by default
     #[arg(short, long)]
     pub year: Option<i32>,
+    /// Set formatted output path for the examples, `{{n}}` being the example number
+    #[arg(
+        short = 'o',
+        long,
+        value_name = "PATTERN",
+        long_help = r#"Set formatted output path for the examples
+Valid subtituted tokens: same as `yaadv -I --help`, along with `{{n}}`
+Examples are saved next to the inputs by default, for eg. "./inputs/2022/day1.example1""#
+    )]
+    pub formatted_path: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct Submit {
+    #[arg(short, long)]
+    pub day: u32,
+    /// Current AOC year by default
+    #[arg(short, long)]
+    pub year: Option<i32>,
+    /// Puzzle part, either 1 or 2
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub part: u8,
+    pub answer: String,
+    /// Submit even if the answer is already known to be wrong from earlier submissions
+    #[arg(short, long)]
+    pub force: bool,
+    /// Wait for any submission cooldown to pass, then submit automatically
+    #[arg(short, long)]
+    pub wait: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct Inputs {
+    /// Days to fetch, for eg. `1-5,9,12-`; all days of the year by default
+    #[arg(short, long, value_name = "RANGE")]
+    pub day: Option<RangeList>,
+    /// Years to fetch, for eg. `2015-2022` or `all`; current AOC year by default
+    #[arg(short, long, value_name = "RANGE")]
+    pub year: Option<RangeList>,
     /// Set formatted output path for fetched inputs
     #[arg(
         short = 'o',
         long,
         value_name = "PATTERN",
         long_help = r#"Set formatted output path for fetched inputs
-Valid subtituted tokens: `{{day}}`, `{{year}}`
-For eg. `yaadv -Id 1 -y 2022 -p "./inputs/day{{day}}.input"` will generate "./inputs/day1.input""#
+Valid subtituted tokens: `{{day}}`, `{{year}}`, `{{part}}`, `{{kind}}`, `{{user}}`, `{{env:VAR}}`
+Tokens can be padded to a width, with a leading 0 for zeros, for eg. `{{day:02}}`
+For eg. `yaadv -Id 1 -y 2022 -o "./inputs/day{{day:02}}.input"` will generate "./inputs/day01.input""#
     )]
     pub formatted_path: Option<String>,
-    /// Makes sure config exists in pwd
+    /// Number of concurrent downloads [default: 4]
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+    /// Minimum delay between requests in milliseconds [default: 250]
+    #[arg(long, value_name = "MS")]
+    pub delay: Option<u64>,
+    /// Number of retries for transient failures, like a 5xx or a timeout [default: 3]
+    #[arg(long, value_name = "N")]
+    pub retries: Option<u32>,
+    /// Only use inputs from the local cache, without touching the network
+    #[arg(long)]
+    pub offline: bool,
+    /// Wait for the next puzzle to unlock, then fetch its input right away
+    #[arg(short, long, conflicts_with_all = ["day", "year", "offline"])]
+    pub wait: bool,
+    /// Re-download inputs even if they already exist
+    #[arg(short, long)]
+    pub force: bool,
+    /// Makes sure a project config exists in pwd or a parent folder, up to the git root
     #[arg(long)]
     pub config_exists: bool,
 }
 
 #[derive(Debug, Args, Default, PartialEq, Eq)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Credentials {
+    #[command(subcommand)]
+    pub command: Option<CredentialsCommand>,
     /// Show stored session token
-    #[arg(short, long, exclusive = true)]
+    #[arg(short, long, conflicts_with_all = ["token", "token_stdin", "no_verify", "check"])]
     pub show: bool,
     /// Set session token
-    #[arg(short, long, exclusive = true)]
+    #[arg(short, long, conflicts_with = "token_stdin")]
     pub token: Option<String>,
+    /// Set session token, reading it from stdin
+    #[arg(long)]
+    pub token_stdin: bool,
+    /// Store the session token without checking it with AOC first
+    #[arg(long)]
+    pub no_verify: bool,
+    /// Check that the session token is still valid, exiting with 1 if it isn't
+    #[arg(short, long, conflicts_with_all = ["token", "token_stdin", "no_verify"])]
+    pub check: bool,
+}
+
+impl Credentials {
+    /// Whether no action was given, in which case the interactive mode is used
+    pub fn is_interactive(&self) -> bool {
+        self.command.is_none()
+            && !self.show
+            && !self.check
+            && !self.token_stdin
+            && self.token.is_none()
+    }
+}
+
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum CredentialsCommand {
+    /// Add a profile, or replace its session token; prompts for the token if none is given
+    Add {
+        name: String,
+        #[arg(short, long, conflicts_with = "token_stdin")]
+        token: Option<String>,
+        /// Read the session token from stdin
+        #[arg(long)]
+        token_stdin: bool,
+        /// Store the session token without checking it with AOC first
+        #[arg(long)]
+        no_verify: bool,
+    },
+    /// Import the session cookie from a local browser profile, you must be logged in to AOC there
+    Import {
+        #[arg(short, long, value_enum)]
+        browser: Browser,
+        /// Browser profile folder to read from [default: the browser's default profile]
+        #[arg(long, value_name = "PATH")]
+        profile_dir: Option<PathBuf>,
+        /// Store the session token without checking it with AOC first
+        #[arg(long)]
+        no_verify: bool,
+    },
+    /// Make a profile the one used by default
+    Use { name: String },
+    /// List the profiles, marking the one in use
+    List,
+    /// Remove a profile along with its session token
+    Remove { name: String },
+    /// Encrypt the stored secrets with a passphrase, taken from `YAADV_PASSPHRASE` or prompted for
+    Encrypt,
+    /// Store the secrets in plaintext again
+    Decrypt,
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Show the effective config values
+    Show {
+        /// Also show which layer each value comes from
+        #[arg(long)]
+        origin: bool,
+    },
+    /// Create an annotated `.yaadv.ron` in pwd
+    Init {
+        /// Overwrite the existing config
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Print the effective value of a key
+    Get { key: String },
+    /// Set a key in the project config, creating `.yaadv.ron` in pwd if there's none
+    Set {
+        key: String,
+        value: String,
+        /// Set it in the global config instead
+        #[arg(short, long)]
+        global: bool,
+    },
+    /// Check the project and global configs for errors
+    Validate {
+        /// Config file to check instead
+        file: Option<PathBuf>,
+    },
 }
@@ -1,56 +1,215 @@
-use anyhow::{bail, Context, Result};
-use chrono::Datelike;
+use anyhow::{anyhow, bail, Context, Result};
 use clap::Parser;
 use colored::*;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::{fmt, fs, io::Write, process, time::Duration};
-use yaadv::{api::fetch_inputs, args::Cli, config::Config, credentials::Secrets, inputs::AdvInput};
-
-fn download_inputs(inputs: &Vec<AdvInput>, session_token: &str) -> Result<Vec<String>> {
-    fs::create_dir_all(
-        inputs
-            .iter()
-            .next()
-            .context("no input file to download")?
-            .path()
-            .parent()
-            .context("no parent folder exists")?,
-    )?;
-
-    let mut out_err = vec![];
-
-    for (input, resp) in fetch_inputs(inputs, session_token)
-        .into_iter()
-        .enumerate()
-        .map(|(i, resp)| (&inputs[i], resp))
-    {
-        match resp {
-            Ok(resp) => {
-                fs::File::create(input.path())?.write_all(resp.into_string()?.as_bytes())?
-            }
-            Err(err) => {
-                if let ureq::Error::Status(err_code, _) = err {
-                    if err_code == 404 {
-                        out_err.push(format!(
-                            "{} {} {} {}",
-                            "Error 404:".red(),
-                            "Day".red(),
-                            input.day.to_string().red(),
-                            "is either not unlocked yet or doesn't exist".red()
-                        ));
-                    } else {
-                        // for any error other than 404; just abort
-                        bail!(
-                            "unhandled error while downloading input files!\n{}",
-                            err.to_string()
-                        )
-                    }
-                }
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::{
+    env, fmt, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process, thread,
+    time::Duration,
+};
+use yaadv::{
+    account::Accounts,
+    answer::Verdict,
+    api::{AocClient, FetchState},
+    args::{Cli, ConfigCommand, CredentialsCommand},
+    cache::Cache,
+    calendar,
+    config::{self, Config, Layered},
+    credentials::{given_session_token, session_token, Secrets},
+    defines::{
+        APP_CONFIG_PATH, APP_SECRETS_PATH, DEFAULT_BASE_URL, DEFAULT_MIN_DELAY, DEFAULT_RETRIES,
+        DEFAULT_WORKERS, ENV_PASSPHRASE,
+    },
+    history::History,
+    inputs::{AdvInput, Kind},
+    problem::Problem,
+    range::RangeList,
+    template,
+    user::User,
+};
+
+/// Effective base URL, the default one if no config layer sets it
+fn base_url(cfg: &Config) -> String {
+    cfg.base_url
+        .clone()
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+}
+
+/// Returns the inputs that failed to download, along with the reason
+fn download_inputs<'a>(
+    client: &AocClient,
+    inputs: &'a [AdvInput<'a>],
+) -> Result<Vec<(&'a AdvInput<'a>, yaadv::Error)>> {
+    let mp = MultiProgress::new();
+    let style = ProgressStyle::with_template("{spinner:.blue} {prefix} {msg}")
+        .unwrap()
+        .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏", "✔"]);
+    let bars: Vec<_> = inputs
+        .iter()
+        .map(|input| {
+            let pb = mp.add(ProgressBar::new_spinner());
+            pb.set_style(style.clone());
+            pb.set_prefix(format!("{} Day {:>2}", input.year, input.day));
+            pb.set_message("Queued".dimmed().to_string());
+            pb
+        })
+        .collect();
+
+    let days: Vec<_> = inputs.iter().map(|input| (input.year, input.day)).collect();
+    let resps = client.inputs(&days, |idx, state| {
+        let pb = &bars[idx];
+        match state {
+            FetchState::Fetching => {
+                pb.enable_steady_tick(Duration::from_millis(80));
+                pb.set_message("Downloading...");
             }
+            FetchState::Retrying(attempt) => pb.set_message(
+                format!("Retrying... (attempt {})", attempt)
+                    .yellow()
+                    .to_string(),
+            ),
+            FetchState::Done => pb.finish_with_message("Done".green().to_string()),
+            FetchState::Failed => pb.finish_with_message("Failed".red().to_string()),
+        }
+    });
+    mp.clear()?;
+
+    let mut failed = vec![];
+
+    for (input, resp) in inputs.iter().zip(resps) {
+        match resp {
+            Ok(body) => input.write(&body)?,
+            Err(err) => failed.push((input, err)),
         };
     }
 
-    Ok(out_err)
+    Ok(failed)
+}
+
+/// Formats as `[Nd ]HH:MM:SS`
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let hms = format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    );
+    match secs / 86400 {
+        0 => hms,
+        days => format!("{}d {}", days, hms),
+    }
+}
+
+/// Shows a live countdown until the given puzzle unlocks
+fn wait_for_unlock(year: i32, day: u32) {
+    countdown(&format!("{} Day {} unlocks in", year, day), || {
+        calendar::time_until_unlock(year, day)
+    })
+}
+
+/// Shows a live countdown until `left` runs out
+fn countdown(msg: &str, left: impl Fn() -> Option<Duration>) {
+    let sp = ProgressBar::new_spinner();
+    sp.set_style(
+        ProgressStyle::with_template("{spinner:.blue} {msg}")
+            .unwrap()
+            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]),
+    );
+    sp.enable_steady_tick(Duration::from_millis(80));
+    while let Some(left) = left() {
+        sp.set_message(format!("{} {}", msg, format_duration(left).yellow()));
+        thread::sleep(left.min(Duration::from_millis(200)));
+    }
+    sp.finish_and_clear();
+}
+
+/// Fetches the input of a puzzle that just unlocked, retrying on the expected early 404s
+fn fetch_on_unlock(client: &AocClient, year: i32, day: u32) -> yaadv::Result<String> {
+    let sp = ProgressBar::new_spinner();
+    sp.set_message(format!("Downloading {} Day {}...", year, day));
+    sp.enable_steady_tick(Duration::from_millis(80));
+    let mut attempt = 0;
+    let resp = loop {
+        // small random delay, to not hit AOC at the exact same moment as everyone else
+        thread::sleep(Duration::from_millis(fastrand::u64(500..2500)));
+        match client.input(year, day) {
+            Err(yaadv::Error::NotUnlocked) if attempt < 5 => attempt += 1,
+            resp => break resp,
+        }
+    };
+    sp.finish_and_clear();
+    resp
+}
+
+/// Error for commands that can't do without a session token
+fn no_session_token() -> anyhow::Error {
+    anyhow!("No session token found!\nPlease add a session token first")
+}
+
+fn fetch_problem(
+    base_url: String,
+    session_token: Option<String>,
+    year: i32,
+    day: u32,
+) -> Result<Problem> {
+    if !calendar::is_puzzle(year, day) {
+        bail!("Day {} of {} doesn't exist", day, year);
+    }
+    if !calendar::is_unlocked(year, day) {
+        bail!("Day {} of {} is not unlocked yet", day, year);
+    }
+
+    let mut client = AocClient::builder().base_url(base_url);
+    // part two is only visible with a session
+    if let Some(token) = session_token {
+        client = client.session_token(token);
+    }
+    let problem = client.build().problem(year, day)?;
+    if problem.parts() == 0 {
+        bail!("Could not find the problem statement");
+    }
+    Ok(problem)
+}
+
+/// Checks the session token with AOC, returning the account it belongs to
+fn verify_token(base_url: String, token: &str) -> Result<User> {
+    let sp = ProgressBar::new_spinner();
+    sp.set_message("Checking session token...");
+    sp.enable_steady_tick(Duration::from_millis(80));
+    let user = AocClient::builder()
+        .base_url(base_url)
+        .session_token(token)
+        .build()
+        .user();
+    sp.finish_and_clear();
+    Ok(user?)
+}
+
+fn describe_user(user: &User) -> String {
+    match user.id {
+        Some(id) => format!("{} (#{})", user.name, id),
+        None => user.name.clone(),
+    }
+}
+
+fn prompt_token() -> Result<String> {
+    Ok(inquire::Password::new("Your session token:")
+        .with_display_mode(inquire::PasswordDisplayMode::Masked)
+        .without_confirmation()
+        .prompt()?)
+}
+
+fn read_token_stdin() -> Result<String> {
+    let mut token = String::new();
+    io::stdin().read_to_string(&mut token)?;
+    let token = token.trim();
+    if token.is_empty() {
+        bail!("No session token was given on stdin");
+    }
+    Ok(token.to_string())
 }
 
 #[derive(Debug)]
@@ -70,145 +229,640 @@ impl fmt::Display for CredentialsOption {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    // global flags, overriding every other config layer
+    let overrides = Config {
+        base_url: cli.base_url,
+        profile: cli.profile,
+        ..Default::default()
+    };
 
     match cli.command {
-        yaadv::args::Commands::Inputs(inputs) => {
-            let cfg = Config::load();
-            if inputs.config_exists {
-                if cfg.is_none() {
-                    eprintln!("{}", "Could not find the config file in pwd".red());
-                    process::exit(2);
+        yaadv::args::Commands::Inputs(args) => {
+            let layered = Layered::load(Config {
+                path: args.formatted_path,
+                jobs: args.jobs,
+                delay: args.delay,
+                retries: args.retries,
+                ..overrides
+            })?;
+            if args.config_exists && layered.project.is_none() {
+                eprintln!(
+                    "{}",
+                    "Could not find the config file in pwd or any parent folder".red()
+                );
+                process::exit(2);
+            }
+            let cfg = layered.config;
+            let profile = Secrets::resolve_profile(cfg.profile.as_deref())?;
+            // a path from the project config is relative to its folder
+            let (formatted_path, base_dir) = (cfg.path.as_deref(), cfg.dir.as_deref());
+
+            let mut inputs = vec![];
+            let mut locked = 0;
+            if args.wait {
+                let (year, day) = calendar::next_unlock();
+                inputs.push(
+                    AdvInput::new(day, year)
+                        .with_user(&profile)
+                        .with_formatted_path(formatted_path)
+                        .with_base_dir(base_dir),
+                );
+            } else {
+                let latest_year = calendar::latest_year();
+                let years = match &args.year {
+                    Some(years) => {
+                        years.resolve(calendar::FIRST_YEAR as u32, latest_year as u32)?
+                    }
+                    None => vec![latest_year as u32],
+                };
+                let days = args.day.unwrap_or_else(RangeList::all);
+
+                for year in years {
+                    let year = year as i32;
+                    for day in days
+                        .resolve(1, calendar::days_in(year))
+                        .with_context(|| format!("invalid days for {}", year))?
+                    {
+                        if !calendar::is_unlocked(year, day) {
+                            locked += 1;
+                            continue;
+                        }
+                        inputs.push(
+                            AdvInput::new(day, year)
+                                .with_user(&profile)
+                                .with_formatted_path(formatted_path)
+                                .with_base_dir(base_dir),
+                        );
+                    }
                 }
             }
-            let cfg = cfg.unwrap_or_default();
 
-            let days = if let Some(day) = inputs.day {
-                vec![day]
+            if locked > 0 {
+                eprintln!(
+                    "{}",
+                    format!("Skipping {} day[s] that aren't unlocked yet", locked).dimmed()
+                );
+            }
+
+            // catch invalid patterns before anything gets downloaded
+            for input in &inputs {
+                input.path()?;
+            }
+
+            let (skipped, inputs): (Vec<_>, Vec<_>) = inputs
+                .into_iter()
+                .partition(|input| !args.force && input.is_downloaded());
+            if !skipped.is_empty() {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "Skipping {} already downloaded input file[s], use --force to re-download",
+                        skipped.len()
+                    )
+                    .dimmed()
+                );
+            }
+            if inputs.is_empty() {
+                return Ok(());
+            }
+
+            let session_token = match args.offline {
+                // the stored token is only used to find the cached inputs of its account, the
+                // profile's last account does just as well without unlocking the secrets
+                true => given_session_token(cli.token_file.as_deref())?,
+                false => session_token(cli.token_file.as_deref(), &profile)?,
+            };
+            let mut client = AocClient::builder();
+            match &session_token {
+                Some(token) => client = client.session_token(token),
+                // inputs can still be served from the cache
+                None if args.offline => (),
+                None => return Err(no_session_token()),
+            }
+            let account = match &session_token {
+                Some(token) if !args.offline => {
+                    Some(Accounts::resolve(&profile, token, &base_url(&cfg)))
+                }
+                // don't ask AOC who the token belongs to while offline
+                token => {
+                    let accounts = Accounts::load()?;
+                    token
+                        .as_deref()
+                        .and_then(|token| accounts.for_token(token))
+                        .or_else(|| accounts.for_profile(&profile))
+                        .map(str::to_string)
+                }
+            };
+            let cache = account.as_deref().and_then(Cache::for_account);
+            match cache {
+                Some(cache) => client = client.cache(cache),
+                None if args.offline => bail!("No cached inputs found to use while offline"),
+                None => (),
+            }
+            let client = client
+                .refresh(args.force)
+                .offline(args.offline)
+                .base_url(base_url(&cfg))
+                .workers(cfg.jobs.unwrap_or(DEFAULT_WORKERS))
+                .min_delay(cfg.delay.map_or(DEFAULT_MIN_DELAY, Duration::from_millis))
+                .retries(cfg.retries.unwrap_or(DEFAULT_RETRIES))
+                .build();
+            let failed = if args.wait {
+                let input = &inputs[0];
+                wait_for_unlock(input.year, input.day);
+                match fetch_on_unlock(&client, input.year, input.day) {
+                    Ok(body) => {
+                        input.write(&body)?;
+                        vec![]
+                    }
+                    Err(err) => vec![(input, err)],
+                }
             } else {
-                (1..=25).collect()
+                download_inputs(&client, &inputs)?
             };
 
-            let year = if let Some(year) = inputs.year {
-                year
+            for (input, err) in &failed {
+                eprintln!(
+                    "{} {}",
+                    format!("{} Day {}:", input.year, input.day).red(),
+                    err.to_string().red()
+                );
+            }
+            if failed.len() == inputs.len() {
+                bail!("Could not download any input file");
+            }
+            if !failed.is_empty() {
+                eprintln!(
+                    "{} {}",
+                    "Failed to download day[s]:".red(),
+                    failed
+                        .iter()
+                        .map(|(input, _)| format!("{}/{}", input.year, input.day))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                        .red()
+                );
+            }
+            // report where the first input that was actually written went
+            let written = inputs.iter().find(|input| {
+                !failed
+                    .iter()
+                    .any(|(failed, _)| (failed.year, failed.day) == (input.year, input.day))
+            });
+            if let Some(path) = written.and_then(|input| input.path().ok()) {
+                let dir = path.parent().unwrap_or(&path);
+                eprintln!(
+                    "{} {}",
+                    "Done downloading input file[s] in".green(),
+                    fs::canonicalize(dir)
+                        .unwrap_or_else(|_| dir.to_path_buf())
+                        .to_string_lossy()
+                        .yellow()
+                );
+            }
+        }
+        yaadv::args::Commands::Problem(args) => {
+            let cfg = Layered::load(overrides)?.config;
+            let year = args.year.unwrap_or_else(calendar::latest_year);
+            let profile = Secrets::resolve_profile(cfg.profile.as_deref())?;
+            let problem = fetch_problem(
+                base_url(&cfg),
+                session_token(cli.token_file.as_deref(), &profile)?,
+                year,
+                args.day,
+            )?;
+
+            if args.save {
+                let out = AdvInput::new(args.day, year)
+                    .with_kind(Kind::Problem)
+                    .with_user(&profile)
+                    .with_formatted_path(args.formatted_path.as_deref());
+                out.write(&format!("{}\n", problem.to_markdown()))?;
+                eprintln!(
+                    "{} {}",
+                    "Saved problem statement in".green(),
+                    out.path()?.to_string_lossy().yellow()
+                );
+            } else if args.markdown {
+                println!("{}", problem.to_markdown());
             } else {
-                let curr = chrono::Utc::now().naive_utc();
-                let mut yr = curr.year();
-                // since AOC starts in december
-                if curr.month() != 12 {
-                    yr -= 1;
+                println!("{}", problem.to_text());
+            }
+        }
+        yaadv::args::Commands::Examples(args) => {
+            let cfg = Layered::load(overrides)?.config;
+            let year = args.year.unwrap_or_else(calendar::latest_year);
+            let profile = Secrets::resolve_profile(cfg.profile.as_deref())?;
+            let examples = fetch_problem(
+                base_url(&cfg),
+                session_token(cli.token_file.as_deref(), &profile)?,
+                year,
+                args.day,
+            )?
+            .examples();
+            if examples.is_empty() {
+                bail!("Could not find any example in the problem statement");
+            }
+
+            // save next to the inputs, if their path is customized
+            let (pattern, base_dir) = match (args.formatted_path, &cfg.path) {
+                (Some(pattern), _) => (Some(pattern), None),
+                (None, Some(path)) => (
+                    Some(format!("{}.example{{{{n}}}}", path)),
+                    cfg.dir.as_deref(),
+                ),
+                (None, None) => (None, None),
+            };
+            if let Some(pattern) = &pattern {
+                if examples.len() > 1 && !template::has_token(pattern, "n") {
+                    bail!(
+                        "Found {} examples, but `{}` is missing `{{{{n}}}}` to save them apart",
+                        examples.len(),
+                        pattern
+                    );
+                }
+            }
+            for (idx, example) in examples.iter().enumerate() {
+                let out = AdvInput::new(args.day, year)
+                    .with_kind(Kind::Example(idx + 1))
+                    .with_part(example.part)
+                    .with_user(&profile)
+                    .with_formatted_path(pattern.as_deref())
+                    .with_base_dir(base_dir);
+                out.write(&example.input)?;
+                eprintln!(
+                    "{} {}",
+                    format!("Saved example {} in", idx + 1).green(),
+                    out.path()?.to_string_lossy().yellow()
+                );
+                for (part, answer) in &example.answers {
+                    println!(
+                        "Example {} part {}: {}",
+                        idx + 1,
+                        part,
+                        answer.bright_cyan()
+                    );
+                }
+            }
+        }
+        yaadv::args::Commands::Submit(args) => {
+            let cfg = Layered::load(overrides)?.config;
+            let year = args.year.unwrap_or_else(calendar::latest_year);
+            if !calendar::is_unlocked(year, args.day) {
+                bail!(
+                    "Day {} of {} is either not unlocked yet or doesn't exist",
+                    args.day,
+                    year
+                );
+            }
+            let answer = args.answer.trim();
+            if answer.is_empty() {
+                bail!("Answer can't be empty");
+            }
+
+            let profile = Secrets::resolve_profile(cfg.profile.as_deref())?;
+            let session_token =
+                session_token(cli.token_file.as_deref(), &profile)?.ok_or_else(no_session_token)?;
+            let account = Accounts::resolve(&profile, &session_token, &base_url(&cfg));
+            let client = AocClient::builder()
+                .session_token(session_token)
+                .base_url(base_url(&cfg))
+                .build();
+            let verdict = loop {
+                // hold the lock from checking the history until the verdict is stored, so that
+                // another terminal submitting meanwhile can't slip in between
+                let lock = History::lock(&account)?;
+                let mut history = History::load(&account)?;
+                if !args.force {
+                    if let Err(refusal) = history.check(year, args.day, args.part, answer) {
+                        eprintln!("{}", refusal.to_string().red());
+                        eprintln!("{}", "Use --force to submit anyway".dimmed());
+                        process::exit(15);
+                    }
+                }
+                if let Some(left) = history.cooldown_left() {
+                    drop(lock);
+                    if !args.wait {
+                        eprintln!(
+                            "{}",
+                            format!(
+                                "You have {} left to wait before submitting, use --wait to submit once it's over",
+                                format_duration(left)
+                            )
+                            .yellow()
+                        );
+                        process::exit(14);
+                    }
+                    countdown("Submitting in", || {
+                        History::load(&account)
+                            .ok()
+                            .and_then(|history| history.cooldown_left())
+                    });
+                    continue;
+                }
+
+                let outcome = client.submit(year, args.day, args.part, answer)?;
+                history.record(year, args.day, args.part, answer, outcome.verdict);
+                if let Some(cooldown) = outcome.cooldown {
+                    history.set_cooldown(cooldown);
+                }
+                history.store(&account)?;
+                drop(lock);
+
+                match outcome.verdict {
+                    Verdict::RateLimited(Some(_)) if args.wait => continue,
+                    verdict => break verdict,
                 }
-                yr
             };
 
-            let sp = ProgressBar::new_spinner();
-            sp.set_message("Downloading...");
-            sp.enable_steady_tick(Duration::from_millis(80));
-            sp.set_style(
-                ProgressStyle::with_template("{spinner:.blue} {msg}")
-                    .unwrap()
-                    .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]),
-            );
+            let (msg, code) = match verdict {
+                Verdict::Correct => (verdict.to_string().green(), 0),
+                Verdict::Wrong => (verdict.to_string().red(), 10),
+                Verdict::TooHigh => (verdict.to_string().red(), 11),
+                Verdict::TooLow => (verdict.to_string().red(), 12),
+                Verdict::AlreadySolved => (verdict.to_string().yellow(), 13),
+                Verdict::RateLimited(_) => (verdict.to_string().yellow(), 14),
+            };
+            eprintln!("{}", msg);
+            process::exit(code);
+        }
+        yaadv::args::Commands::Credentials(creds) => {
+            let cfg = Layered::load(overrides)?.config;
+            let base_url = base_url(&cfg);
+            let profile = Secrets::resolve_profile(cfg.profile.as_deref())?;
+            // checks the token before it gets stored, unless told not to
+            let verify =
+                |token: &str, no_verify: bool| -> Result<()> {
+                    if no_verify {
+                        return Ok(());
+                    }
+                    match verify_token(base_url.clone(), token) {
+                    Ok(user) => {
+                        eprintln!("{} {}", "Logged in as".green(), describe_user(&user).yellow());
+                        Ok(())
+                    }
+                    Err(err) => Err(err.context(
+                        "Could not verify the session token, use --no-verify to store it anyway",
+                    )),
+                }
+                };
 
-            let inputs = days
-                .into_iter()
-                .map(|day| {
-                    AdvInput::new(day, year).with_formatted_path(
-                        if inputs.formatted_path.is_some() {
-                            inputs.formatted_path.as_ref().map(|s| s.as_str())
+            match creds.command {
+                Some(CredentialsCommand::Add {
+                    name,
+                    token,
+                    token_stdin,
+                    no_verify,
+                }) => {
+                    let token = match (token, token_stdin) {
+                        (Some(token), _) => token,
+                        (None, true) => read_token_stdin()?,
+                        (None, false) => prompt_token()?,
+                    };
+                    verify(&token, no_verify)?;
+                    let mut secrets = Secrets::load()?;
+                    secrets.set(&name, token)?;
+                    secrets.store()?;
+                    eprintln!("{} {}", "Added profile".green(), name.yellow());
+                }
+                Some(CredentialsCommand::Import {
+                    browser,
+                    profile_dir,
+                    no_verify,
+                }) => {
+                    let token = browser.session_cookie(profile_dir.as_deref())?;
+                    verify(&token, no_verify)?;
+                    let mut secrets = Secrets::load()?;
+                    secrets.set(&profile, token)?;
+                    secrets.store()?;
+                    eprintln!(
+                        "{} {}",
+                        "Imported session token into profile".green(),
+                        profile.yellow()
+                    );
+                }
+                Some(CredentialsCommand::Use { name }) => {
+                    let mut secrets = Secrets::load()?;
+                    if secrets.get(&name).is_none() {
+                        bail!("No profile named `{}`, see `yaadv credentials list`", name);
+                    }
+                    secrets.active = Some(name.clone());
+                    secrets.store()?;
+                    eprintln!("{} {}", "Now using profile".green(), name.yellow());
+                }
+                Some(CredentialsCommand::List) => {
+                    let secrets = Secrets::load()?;
+                    if secrets.profiles.is_empty() {
+                        eprintln!("{}", "No profiles found!".red());
+                        process::exit(1);
+                    }
+                    for name in secrets.profiles.keys() {
+                        if *name == profile {
+                            println!("* {}", name.green());
                         } else {
-                            // try to use path from cfg located in pwd
-                            cfg.path.as_ref().map(|s| s.as_str())
-                        },
+                            println!("  {}", name);
+                        }
+                    }
+                }
+                Some(CredentialsCommand::Remove { name }) => {
+                    let mut secrets = Secrets::load()?;
+                    if secrets.remove(&name).is_none() {
+                        bail!("No profile named `{}`, see `yaadv credentials list`", name);
+                    }
+                    secrets.store()?;
+                    eprintln!("{} {}", "Removed profile".green(), name.yellow());
+                }
+                Some(CredentialsCommand::Encrypt) => {
+                    let mut secrets = Secrets::load()?;
+                    if secrets.is_encrypted() {
+                        bail!("Secrets are already encrypted");
+                    }
+                    let passphrase = match env::var(ENV_PASSPHRASE) {
+                        Ok(passphrase) => passphrase,
+                        Err(_) => inquire::Password::new("New passphrase:")
+                            .with_display_mode(inquire::PasswordDisplayMode::Masked)
+                            .prompt()?,
+                    };
+                    if passphrase.is_empty() {
+                        bail!("Passphrase can't be empty");
+                    }
+                    secrets.encrypt(passphrase);
+                    secrets.store()?;
+                    eprintln!(
+                        "{} {}",
+                        "Encrypted secrets in".green(),
+                        APP_SECRETS_PATH.to_string_lossy().yellow()
+                    );
+                }
+                Some(CredentialsCommand::Decrypt) => {
+                    let mut secrets = Secrets::load()?;
+                    if !secrets.is_encrypted() {
+                        bail!("Secrets aren't encrypted");
+                    }
+                    secrets.decrypt();
+                    secrets.store()?;
+                    eprintln!(
+                        "{} {}",
+                        "Decrypted secrets in".green(),
+                        APP_SECRETS_PATH.to_string_lossy().yellow()
+                    );
+                }
+                None if creds.is_interactive() => {
+                    // default interactive mode
+
+                    let choice = inquire::Select::new(
+                        &format!("Credentials ({}):", profile),
+                        vec![CredentialsOption::ViewToken, CredentialsOption::SetToken],
                     )
-                })
-                .collect();
-
-            let errs = download_inputs(
-                &inputs,
-                &Secrets::load()
-                    .session_token
-                    .context("No session token found!\nPlease add a sesssion token first")?,
-            )?;
+                    .prompt()?;
 
-            sp.finish_and_clear();
-            errs.into_iter().for_each(|err| eprintln!("{}", err));
-            eprintln!(
-                "{} {}",
-                "Done downloading input file[s] in".green(),
-                fs::canonicalize(
-                    inputs[0]
-                        .path()
-                        .parent()
-                        .context("no parent folder exists")?
-                )?
-                .to_string_lossy()
-                .yellow()
-            );
-        }
-        yaadv::args::Commands::Credentials(creds) => {
-            if creds == Default::default() {
-                // default interactive mode
-
-                let choice = inquire::Select::new(
-                    "Credentials:",
-                    vec![CredentialsOption::ViewToken, CredentialsOption::SetToken],
-                )
-                .prompt()?;
-
-                match choice {
-                    CredentialsOption::ViewToken => {
-                        let token = Secrets::load();
-                        match token.get_session_token() {
+                    let mut secrets = Secrets::load()?;
+                    match choice {
+                        CredentialsOption::ViewToken => match secrets.get(&profile) {
                             Some(token) => println!("Your session token: {}", token.bright_cyan()),
                             None => {
                                 eprintln!("{}", "No session token found!".red());
                                 process::exit(1);
                             }
+                        },
+                        CredentialsOption::SetToken => {
+                            let token = prompt_token()?;
+                            verify(&token, creds.no_verify)?;
+
+                            if secrets.get(&profile).is_some() {
+                                let confirm = inquire::Confirm::new(
+                                    "Your previous session token will be overwritten, continue?",
+                                )
+                                .with_default(false)
+                                .prompt()?;
+                                if !confirm {
+                                    process::exit(0);
+                                }
+                            }
+                            secrets.set(&profile, token)?;
+                            secrets.store()?;
                         }
                     }
-                    CredentialsOption::SetToken => {
-                        let token = inquire::Password::new("Your session token:")
-                            .with_display_mode(inquire::PasswordDisplayMode::Masked)
-                            .without_confirmation()
-                            .prompt()?;
-
-                        let old_token = Secrets::load();
-                        if old_token.get_session_token().is_some() {
-                            let confirm = inquire::Confirm::new(
-                                "Your previous session token will be overwritten, continue?",
-                            )
-                            .with_default(false)
-                            .prompt()?;
-                            if !confirm {
-                                process::exit(0);
+                }
+                None => {
+                    let token = match creds.token_stdin {
+                        true => Some(read_token_stdin()?),
+                        false => creds.token,
+                    };
+                    if let Some(token) = token {
+                        verify(&token, creds.no_verify)?;
+                        let mut secrets = Secrets::load()?;
+                        secrets.set(&profile, token)?;
+                        secrets.store()?;
+                    } else if creds.check {
+                        let token = session_token(cli.token_file.as_deref(), &profile)?
+                            .ok_or_else(no_session_token)?;
+                        let user = verify_token(base_url, &token)?;
+                        eprintln!(
+                            "{} {}",
+                            "Session token is valid, logged in as".green(),
+                            describe_user(&user).yellow()
+                        );
+                    } else if creds.show {
+                        match Secrets::load()?.get(&profile) {
+                            Some(token) => println!("Your session token: {}", token.bright_cyan()),
+                            None => {
+                                eprintln!("{}", "No session token found!".red());
+                                process::exit(1);
                             }
                         }
-                        Secrets {
-                            session_token: Some(token),
+                    }
+                }
+            }
+        }
+        yaadv::args::Commands::Config(args) => match args.command {
+            ConfigCommand::Show { origin } => {
+                let layered = Layered::load(overrides)?;
+                for key in Config::KEYS {
+                    let value = match layered.config.get(key)? {
+                        Some(value) => value.bright_cyan(),
+                        None => "<unset>".dimmed(),
+                    };
+                    match layered.origin(key) {
+                        Some(from) if origin => {
+                            println!("{} = {} {}", key, value, format!("({})", from).dimmed())
                         }
-                        .store()?;
+                        _ => println!("{} = {}", key, value),
                     }
                 }
             }
-
-            if let Some(token) = creds.token {
-                Secrets {
-                    session_token: Some(token),
+            ConfigCommand::Init { force } => {
+                let file = Path::new(".yaadv.ron");
+                if file.exists() && !force {
+                    bail!(
+                        "{} already exists, use --force to overwrite it",
+                        file.display()
+                    );
                 }
-                .store()?;
+                fs::write(file, config::TEMPLATE)?;
+                eprintln!(
+                    "{} {}",
+                    "Created config in".green(),
+                    fs::canonicalize(file)?.to_string_lossy().yellow()
+                );
             }
-
-            if creds.show {
-                let token = Secrets::load();
-                match token.get_session_token() {
-                    Some(token) => println!("Your session token: {}", token.bright_cyan()),
-                    None => {
-                        eprintln!("{}", "No session token found!".red());
-                        process::exit(1);
+            ConfigCommand::Get { key } => {
+                let layered = Layered::load(overrides)?;
+                match layered.config.get(&key)? {
+                    Some(value) => println!("{}", value),
+                    None => process::exit(1),
+                }
+            }
+            ConfigCommand::Set { key, value, global } => {
+                let file = match (global, Config::find()?) {
+                    (true, _) => APP_CONFIG_PATH.clone(),
+                    (false, Some(file)) => file,
+                    (false, None) => PathBuf::from(".yaadv.ron"),
+                };
+                let mut cfg = match file.is_file() {
+                    true => Config::read(&file)?,
+                    false => Config::default(),
+                };
+                cfg.set(&key, &value)?;
+                let had_comments = fs::read_to_string(&file)
+                    .is_ok_and(|body| body.contains("//") || body.contains('#'));
+                let in_place = cfg.write_key(&file, &key)?;
+                eprintln!(
+                    "{} {}",
+                    format!("Set {} in", key).green(),
+                    file.to_string_lossy().yellow()
+                );
+                if had_comments && !in_place {
+                    eprintln!("{}", "Comments in the config file weren't kept".dimmed());
+                }
+            }
+            ConfigCommand::Validate { file } => {
+                let files: Vec<_> = match file {
+                    Some(file) => vec![file],
+                    None => [Some(APP_CONFIG_PATH.clone()), Config::find()?]
+                        .into_iter()
+                        .flatten()
+                        .filter(|file| file.is_file())
+                        .collect(),
+                };
+                if files.is_empty() {
+                    eprintln!("{}", "No config file found".yellow());
+                }
+                let mut invalid = false;
+                for file in files {
+                    match Config::read(&file) {
+                        Ok(_) => eprintln!("{} {}", "✔".green(), file.to_string_lossy()),
+                        Err(err) => {
+                            invalid = true;
+                            eprintln!("{} {}", "✘".red(), err.to_string().red());
+                        }
                     }
                 }
+                if invalid {
+                    process::exit(1);
+                }
             }
-        }
+        },
     }
 
     Ok(())
@@ -0,0 +1,351 @@
+use crate::{Error, Result};
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use clap::ValueEnum;
+use rusqlite::{Connection, OptionalExtension};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+/// Hosts the AOC session cookie can be stored under
+const HOSTS: [&str; 2] = [".adventofcode.com", "adventofcode.com"];
+
+/// Browser to import the session cookie from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Browser {
+    Firefox,
+    Chromium,
+    Chrome,
+}
+
+impl Browser {
+    /// Reads the AOC session cookie from the browser's cookie database.
+    ///
+    /// `profile_dir` is the browser profile folder, the default profile is used if not given.
+    pub fn session_cookie(self, profile_dir: Option<&Path>) -> Result<String> {
+        let profile_dir = match profile_dir {
+            Some(dir) => dir.to_path_buf(),
+            None => self.default_profile()?,
+        };
+        let cookies = self.cookies_db(&profile_dir)?;
+        // the browser keeps its database locked while running, so read from a copy of it
+        let tmp = TempCopy::new(&cookies)?;
+        let db = Connection::open(&tmp.path)?;
+        let cookie = match self {
+            Browser::Firefox => firefox_cookie(&db)?,
+            Browser::Chromium | Browser::Chrome => chromium_cookie(&db)?,
+        };
+        cookie.filter(|cookie| !cookie.is_empty()).ok_or_else(|| {
+            Error::CookieImport(format!(
+                "no AOC session cookie found in {}, make sure you're logged in to AOC in that profile",
+                cookies.display()
+            ))
+        })
+    }
+    fn name(self) -> &'static str {
+        match self {
+            Browser::Firefox => "Firefox",
+            Browser::Chromium => "Chromium",
+            Browser::Chrome => "Chrome",
+        }
+    }
+    fn cookies_db(self, profile_dir: &Path) -> Result<PathBuf> {
+        let candidates = match self {
+            Browser::Firefox => vec![profile_dir.join("cookies.sqlite")],
+            // newer versions moved it into `Network/`
+            Browser::Chromium | Browser::Chrome => vec![
+                profile_dir.join("Network").join("Cookies"),
+                profile_dir.join("Cookies"),
+            ],
+        };
+        candidates
+            .into_iter()
+            .find(|file| file.is_file())
+            .ok_or_else(|| {
+                Error::CookieImport(format!(
+                    "no {} cookie database found in {}",
+                    self.name(),
+                    profile_dir.display()
+                ))
+            })
+    }
+    fn default_profile(self) -> Result<PathBuf> {
+        let not_found = || {
+            Error::CookieImport(format!(
+                "could not find the default {} profile, use --profile-dir to point to it",
+                self.name()
+            ))
+        };
+        match self {
+            Browser::Firefox => {
+                let root = firefox_root().ok_or_else(not_found)?;
+                let ini = fs::read_to_string(root.join("profiles.ini")).map_err(|_| not_found())?;
+                firefox_default_profile(&ini)
+                    .map(|path| root.join(path))
+                    .ok_or_else(not_found)
+            }
+            Browser::Chromium | Browser::Chrome => {
+                let dir = chromium_root(self).ok_or_else(not_found)?.join("Default");
+                if dir.is_dir() {
+                    Ok(dir)
+                } else {
+                    Err(not_found())
+                }
+            }
+        }
+    }
+}
+
+fn firefox_root() -> Option<PathBuf> {
+    if cfg!(target_os = "linux") {
+        Some(dirs::home_dir()?.join(".mozilla").join("firefox"))
+    } else if cfg!(target_os = "macos") {
+        Some(dirs::data_dir()?.join("Firefox"))
+    } else {
+        Some(dirs::data_dir()?.join("Mozilla").join("Firefox"))
+    }
+}
+
+fn chromium_root(browser: Browser) -> Option<PathBuf> {
+    let dir = match (browser, cfg!(target_os = "linux")) {
+        (Browser::Chrome, true) => "google-chrome",
+        (Browser::Chrome, false) => "Google/Chrome",
+        (_, true) => "chromium",
+        (_, false) => "Chromium",
+    };
+    if cfg!(target_os = "linux") {
+        Some(dirs::config_dir()?.join(dir))
+    } else if cfg!(target_os = "macos") {
+        Some(dirs::data_dir()?.join(dir))
+    } else {
+        Some(dirs::data_local_dir()?.join(dir).join("User Data"))
+    }
+}
+
+/// Path of the default profile from Firefox's `profiles.ini`, relative to the Firefox folder
+/// unless it's absolute.
+///
+/// The profile of an `[Install…]` section wins, as that's the one Firefox actually opens, then the
+/// profile marked with `Default=1`, then the first one.
+fn firefox_default_profile(ini: &str) -> Option<PathBuf> {
+    let mut install = None;
+    // `(path, marked as default)` of every `[Profile…]` section
+    let mut profiles: Vec<(Option<&str>, bool)> = vec![];
+    let mut section = "";
+    for line in ini.lines().map(str::trim) {
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = name;
+            if section.starts_with("Profile") {
+                profiles.push((None, false));
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match (section, key.trim(), profiles.last_mut()) {
+            (section, "Default", _) if section.starts_with("Install") => {
+                install = install.or(Some(value.trim()))
+            }
+            (section, "Default", Some(profile)) if section.starts_with("Profile") => {
+                profile.1 = value.trim() == "1"
+            }
+            (section, "Path", Some(profile)) if section.starts_with("Profile") => {
+                profile.0 = Some(value.trim())
+            }
+            _ => (),
+        }
+    }
+    let marked = profiles.iter().find(|(_, default)| *default);
+    install
+        .or_else(|| marked.and_then(|(path, _)| *path))
+        .or_else(|| profiles.iter().find_map(|(path, _)| *path))
+        .map(PathBuf::from)
+}
+
+fn firefox_cookie(db: &Connection) -> Result<Option<String>> {
+    Ok(db
+        .query_row(
+            "SELECT value FROM moz_cookies WHERE name = 'session' AND host IN (?1, ?2)
+             ORDER BY expiry DESC LIMIT 1",
+            HOSTS,
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+fn chromium_cookie(db: &Connection) -> Result<Option<String>> {
+    let cookie: Option<(String, Vec<u8>)> = db
+        .query_row(
+            "SELECT value, encrypted_value FROM cookies WHERE name = 'session'
+             AND host_key IN (?1, ?2) ORDER BY expires_utc DESC LIMIT 1",
+            HOSTS,
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((value, encrypted)) = cookie else {
+        return Ok(None);
+    };
+    if !value.is_empty() || encrypted.is_empty() {
+        return Ok(Some(value));
+    }
+
+    let version: Option<String> = db
+        .query_row("SELECT value FROM meta WHERE key = 'version'", [], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    let version = version
+        .and_then(|version| version.parse().ok())
+        .unwrap_or(0);
+    decrypt_chromium(&encrypted, version).map(Some)
+}
+
+/// Decrypts a `v10` Chromium cookie, the format used on Linux when no system keyring is in use.
+///
+/// Since database version 24, the plaintext starts with a SHA-256 hash of the cookie's domain.
+fn decrypt_chromium(encrypted: &[u8], db_version: u32) -> Result<String> {
+    let unsupported = |reason: &str| Err(Error::CookieImport(reason.to_string()));
+    if !cfg!(target_os = "linux") {
+        return unsupported("encrypted Chromium cookies can only be imported on Linux");
+    }
+    let Some(ciphertext) = encrypted.strip_prefix(b"v10") else {
+        return unsupported(
+            "the cookie is encrypted with a key from the system keyring, which isn't supported",
+        );
+    };
+
+    let mut key = [0; 16];
+    pbkdf2::pbkdf2_hmac::<sha1::Sha1>(b"peanuts", b"saltysalt", 1, &mut key);
+    let iv = [b' '; 16];
+    let mut buf = ciphertext.to_vec();
+    let plaintext = cbc::Decryptor::<aes::Aes128>::new(&key.into(), &iv.into())
+        .decrypt_padded_mut::<Pkcs7>(&mut buf)
+        .map_err(|_| Error::CookieImport("could not decrypt the cookie".to_string()))?;
+    let plaintext = match db_version {
+        24.. => plaintext.get(32..).unwrap_or_default(),
+        _ => plaintext,
+    };
+    String::from_utf8(plaintext.to_vec())
+        .map_err(|_| Error::CookieImport("decrypted cookie isn't valid UTF-8".to_string()))
+}
+
+/// Copy of a database along with its write-ahead log, removed on drop.
+///
+/// The copy lives in a freshly created temp folder only the current user can access, since the
+/// database holds the cookies of every site.
+struct TempCopy {
+    _dir: TempDir,
+    path: PathBuf,
+}
+
+impl TempCopy {
+    fn new(db: &Path) -> Result<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix("yaadv-cookies-")
+            .tempdir()?;
+        let path = dir.path().join("cookies.db");
+        fs::copy(db, &path)?;
+        let mut wal = db.as_os_str().to_owned();
+        wal.push("-wal");
+        if Path::new(&wal).is_file() {
+            fs::copy(&wal, dir.path().join("cookies.db-wal"))?;
+        }
+        Ok(Self { _dir: dir, path })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn firefox_install_profile_wins() {
+        let ini = "[Profile1]\nName=other\nPath=Profiles/other\nDefault=1\n\n\
+                   [Profile0]\nName=default\nPath=Profiles/abc.default-release\n\n\
+                   [Install4F96D1932A9F858E]\nDefault=Profiles/abc.default-release\nLocked=1\n";
+        assert_eq!(
+            firefox_default_profile(ini),
+            Some(PathBuf::from("Profiles/abc.default-release"))
+        );
+    }
+
+    #[test]
+    fn firefox_marked_profile_wins_over_first() {
+        let ini = "[General]\nStartWithLastProfile=1\n\n\
+                   [Profile0]\nPath=Profiles/first\n\n\
+                   [Profile1]\nDefault=1\nPath=/home/user/ff/marked\n";
+        assert_eq!(
+            firefox_default_profile(ini),
+            Some(PathBuf::from("/home/user/ff/marked"))
+        );
+        // an absolute path stays absolute once joined to the Firefox folder
+        assert_eq!(
+            Path::new("/home/user/.mozilla/firefox").join(firefox_default_profile(ini).unwrap()),
+            Path::new("/home/user/ff/marked")
+        );
+    }
+
+    #[test]
+    fn firefox_falls_back_to_first_profile() {
+        let ini = "[Profile0]\nPath=Profiles/first\n\n[Profile1]\nPath=Profiles/second\n";
+        assert_eq!(
+            firefox_default_profile(ini),
+            Some(PathBuf::from("Profiles/first"))
+        );
+        assert_eq!(firefox_default_profile("[General]\nVersion=2\n"), None);
+    }
+
+    /// Encrypts like Chromium does on Linux without a keyring
+    #[cfg(target_os = "linux")]
+    fn encrypt_chromium(plaintext: &[u8]) -> Vec<u8> {
+        use aes::cipher::BlockEncryptMut;
+
+        let mut key = [0; 16];
+        pbkdf2::pbkdf2_hmac::<sha1::Sha1>(b"peanuts", b"saltysalt", 1, &mut key);
+        let iv = [b' '; 16];
+        let mut buf = plaintext.to_vec();
+        buf.resize(plaintext.len() + 16, 0);
+        let ciphertext = cbc::Encryptor::<aes::Aes128>::new(&key.into(), &iv.into())
+            .encrypt_padded_mut::<Pkcs7>(&mut buf, plaintext.len())
+            .unwrap();
+        [b"v10".as_slice(), ciphertext].concat()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn decrypts_chromium_cookie() {
+        let encrypted = encrypt_chromium(b"53616c7465645f5f");
+        assert_eq!(
+            decrypt_chromium(&encrypted, 23).unwrap(),
+            "53616c7465645f5f"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn strips_domain_hash_from_version_24() {
+        let mut plaintext = vec![0xab; 32];
+        plaintext.extend_from_slice(b"53616c7465645f5f");
+        let encrypted = encrypt_chromium(&plaintext);
+        assert_eq!(
+            decrypt_chromium(&encrypted, 24).unwrap(),
+            "53616c7465645f5f"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn rejects_keyring_cookies() {
+        let mut encrypted = encrypt_chromium(b"token");
+        encrypted[..3].copy_from_slice(b"v11");
+        assert!(matches!(
+            decrypt_chromium(&encrypted, 24),
+            Err(Error::CookieImport(_))
+        ));
+    }
+}
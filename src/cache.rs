@@ -0,0 +1,34 @@
+use crate::{defines::app_cache_dir, utils::write_atomic, Result};
+use std::{fs, path::PathBuf};
+
+/// On-disk cache of puzzle inputs for a single account
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+    /// Cache in the app cache dir for the given account, see [`crate::account::Accounts`]
+    pub fn for_account(account: &str) -> Option<Self> {
+        Some(Self::new(Self::root()?.join(account)))
+    }
+    fn root() -> Option<PathBuf> {
+        Some(app_cache_dir()?.join("inputs"))
+    }
+    fn path(&self, year: i32, day: u32) -> PathBuf {
+        self.dir
+            .join(year.to_string())
+            .join(format!("day{}.input", day))
+    }
+    pub fn get(&self, year: i32, day: u32) -> Option<String> {
+        fs::read_to_string(self.path(year, day))
+            .ok()
+            .filter(|input| !input.is_empty())
+    }
+    pub fn put(&self, year: i32, day: u32, input: &str) -> Result<()> {
+        write_atomic(&self.path(year, day), input)
+    }
+}
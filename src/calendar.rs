@@ -0,0 +1,124 @@
+use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Utc};
+use std::time::Duration;
+
+/// Year of the first Advent of Code event
+pub const FIRST_YEAR: i32 = 2015;
+
+/// Puzzles unlock at midnight EST, which is UTC-5
+fn unlock_offset() -> FixedOffset {
+    FixedOffset::west_opt(5 * 3600).unwrap()
+}
+
+/// Number of puzzles in the given event year
+pub fn days_in(year: i32) -> u32 {
+    // events got shortened to 12 days from 2025 onwards
+    if year >= 2025 {
+        12
+    } else {
+        25
+    }
+}
+
+/// Whether the given puzzle is part of an event, unlocked or not
+pub fn is_puzzle(year: i32, day: u32) -> bool {
+    year >= FIRST_YEAR && (1..=days_in(year)).contains(&day)
+}
+
+/// Instant at which the given puzzle unlocks, or `None` if no such puzzle exists
+pub fn unlock_time(year: i32, day: u32) -> Option<DateTime<Utc>> {
+    if !is_puzzle(year, day) {
+        return None;
+    }
+    unlock_offset()
+        .with_ymd_and_hms(year, 12, day, 0, 0, 0)
+        .single()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+pub fn is_unlocked(year: i32, day: u32) -> bool {
+    is_unlocked_at(year, day, Utc::now())
+}
+
+/// Whether the given puzzle is unlocked at `now`
+pub fn is_unlocked_at(year: i32, day: u32, now: DateTime<Utc>) -> bool {
+    unlock_time(year, day).is_some_and(|time| time <= now)
+}
+
+/// Time left for the given puzzle to unlock, `None` if it's already unlocked or doesn't exist
+pub fn time_until_unlock(year: i32, day: u32) -> Option<Duration> {
+    (unlock_time(year, day)? - Utc::now()).to_std().ok()
+}
+
+/// Latest unlocked puzzle as `(year, day)`
+pub fn latest_unlocked() -> (i32, u32) {
+    latest_unlocked_at(Utc::now())
+}
+
+/// Latest puzzle unlocked at `now` as `(year, day)`
+pub fn latest_unlocked_at(now: DateTime<Utc>) -> (i32, u32) {
+    let now = now.with_timezone(&unlock_offset());
+    if now.month() == 12 {
+        (now.year(), now.day().min(days_in(now.year())))
+    } else {
+        (now.year() - 1, days_in(now.year() - 1))
+    }
+}
+
+/// Next puzzle to unlock as `(year, day)`
+pub fn next_unlock() -> (i32, u32) {
+    next_unlock_at(Utc::now())
+}
+
+/// Next puzzle to unlock after `now` as `(year, day)`
+pub fn next_unlock_at(now: DateTime<Utc>) -> (i32, u32) {
+    let now = now.with_timezone(&unlock_offset());
+    match now.month() {
+        12 if now.day() < days_in(now.year()) => (now.year(), now.day() + 1),
+        12 => (now.year() + 1, 1),
+        _ => (now.year(), 1),
+    }
+}
+
+/// Latest event year that has started
+pub fn latest_year() -> i32 {
+    latest_unlocked().0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn unlocks_at_midnight_est() {
+        assert!(!is_unlocked_at(2022, 1, utc("2022-12-01T04:59:59Z")));
+        assert!(is_unlocked_at(2022, 1, utc("2022-12-01T05:00:00Z")));
+        assert_eq!(latest_unlocked_at(utc("2022-12-01T04:59:59Z")), (2021, 25));
+        assert_eq!(latest_unlocked_at(utc("2022-12-01T05:00:00Z")), (2022, 1));
+    }
+
+    #[test]
+    fn shortened_events() {
+        let later = utc("2026-06-01T00:00:00Z");
+        assert!(is_unlocked_at(2025, 12, later));
+        assert!(!is_unlocked_at(2025, 13, later));
+        assert!(is_unlocked_at(2024, 25, later));
+        assert_eq!(latest_unlocked_at(utc("2025-12-20T12:00:00Z")), (2025, 12));
+        assert_eq!(latest_unlocked_at(later), (2025, 12));
+    }
+
+    #[test]
+    fn next_unlock_rolls_over() {
+        assert_eq!(next_unlock_at(utc("2022-11-30T12:00:00Z")), (2022, 1));
+        assert_eq!(next_unlock_at(utc("2022-12-01T05:00:00Z")), (2022, 2));
+        assert_eq!(next_unlock_at(utc("2022-12-25T05:00:00Z")), (2023, 1));
+        // still December 31st in EST
+        assert_eq!(next_unlock_at(utc("2023-01-01T04:59:59Z")), (2023, 1));
+        assert_eq!(next_unlock_at(utc("2023-01-01T05:00:00Z")), (2023, 1));
+        assert_eq!(next_unlock_at(utc("2025-12-11T05:00:00Z")), (2025, 12));
+        assert_eq!(next_unlock_at(utc("2025-12-12T05:00:00Z")), (2026, 1));
+    }
+}
@@ -1,24 +1,487 @@
+use crate::{
+    defines::{
+        APP_CONFIG_PATH, DEFAULT_BASE_URL, DEFAULT_MIN_DELAY, DEFAULT_RETRIES, DEFAULT_WORKERS,
+        ENV_PREFIX,
+    },
+    Error, Result,
+};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::Path};
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
-    /// Formatted path string
+    /// Formatted path string, relative to the folder holding the config
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
+    /// Base URL for all AOC requests, for eg. a local mock server or a caching proxy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Number of concurrent downloads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
+    /// Minimum delay between requests, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u64>,
+    /// Number of retries for transient failures
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Credentials profile to use
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Folder that a relative `path` is relative to
+    #[serde(skip)]
+    pub dir: Option<PathBuf>,
 }
 
+const CONFIG_FILES: [&str; 5] = [
+    ".yaadv.ron",
+    ".yaadv",
+    ".yaadv.toml",
+    "yaadv.json",
+    "Cargo.toml",
+];
+
+/// Format of a config file, going by its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Ron,
+    Toml,
+    Json,
+    /// `[package.metadata.yaadv]` table of a `Cargo.toml`
+    CargoMetadata,
+}
+
+impl Format {
+    fn of(file: &Path) -> Self {
+        match file.file_name().and_then(|name| name.to_str()) {
+            Some("Cargo.toml") => Format::CargoMetadata,
+            _ => match file.extension().and_then(|ext| ext.to_str()) {
+                Some("toml") => Format::Toml,
+                Some("json") => Format::Json,
+                _ => Format::Ron,
+            },
+        }
+    }
+}
+
+/// Annotated project config written by `yaadv config init`
+pub const TEMPLATE: &str = r#"// yaadv project config, values set here override the global config.
+// Uncomment a field to set it.
+(
+    // Formatted path for the inputs, relative to this folder.
+    // Tokens: {{day}}, {{year}}, {{part}}, {{kind}}, {{user}}, {{env:VAR}}, padded with for eg. {{day:02}}
+    // path: Some("inputs/{{year}}/day{{day:02}}.txt"),
+
+    // Base URL for all AOC requests.
+    // base_url: Some("https://adventofcode.com"),
+
+    // Number of concurrent downloads.
+    // jobs: Some(4),
+
+    // Minimum delay between requests, in milliseconds.
+    // delay: Some(250),
+
+    // Number of retries for transient failures, like a 5xx or a timeout.
+    // retries: Some(3),
+
+    // Credentials profile to use, see `yaadv credentials list`.
+    // profile: Some("default"),
+)
+"#;
+
 impl Config {
-    /// load the `.yaadv.ron` config file from pwd, returns default if fails to load.
-    pub fn load() -> Option<Self> {
-        let cfgs = [".yaadv.ron", ".yaadv"];
-        let mut cfg_file = Path::new(cfgs[0]);
-        for path in cfgs.iter().skip(1) {
-            let cfg = Path::new(path);
-            if cfg.is_file() {
-                cfg_file = cfg;
+    /// All the config keys, in the order they're shown in
+    pub const KEYS: [&'static str; 6] = ["path", "base_url", "jobs", "delay", "retries", "profile"];
+
+    /// Built-in defaults, the lowest config layer
+    pub fn defaults() -> Self {
+        Self {
+            path: None,
+            base_url: Some(DEFAULT_BASE_URL.to_string()),
+            jobs: Some(DEFAULT_WORKERS),
+            delay: Some(DEFAULT_MIN_DELAY.as_millis() as u64),
+            retries: Some(DEFAULT_RETRIES),
+            profile: None,
+            dir: None,
+        }
+    }
+    /// Finds the config file in pwd or its closest parent folder, without going past the root of
+    /// a git repository.
+    ///
+    /// A `Cargo.toml` only counts if it has a `[package.metadata.yaadv]` table. Errors if a folder
+    /// has more than one config file.
+    pub fn find() -> Result<Option<PathBuf>> {
+        let Ok(cwd) = env::current_dir() else {
+            return Ok(None);
+        };
+        for dir in cwd.ancestors() {
+            let found: Vec<_> = CONFIG_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .filter(|file| file.is_file())
+                .filter(|file| Format::of(file) != Format::CargoMetadata || has_metadata(file))
+                .collect();
+            match found.len() {
+                0 => (),
+                1 => return Ok(found.into_iter().next()),
+                _ => {
+                    return Err(Error::Config(format!(
+                        "ambiguous config in {}, found {}; keep only one of them",
+                        dir.display(),
+                        found
+                            .iter()
+                            .filter_map(|file| file.file_name())
+                            .map(|name| name.to_string_lossy())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )))
+                }
+            }
+            if dir.join(".git").exists() {
                 break;
             }
         }
-        Some(ron::from_str(fs::read_to_string(cfg_file).ok()?.as_str()).unwrap_or_default())
+        Ok(None)
+    }
+    /// load the config file found by [`Config::find`], `None` if there's none.
+    pub fn load() -> Result<Option<Self>> {
+        Self::find()?.map(|file| Self::read(&file)).transpose()
+    }
+    /// load the global config from the app config dir, `None` if there's none.
+    pub fn load_global() -> Result<Option<Self>> {
+        if !APP_CONFIG_PATH.is_file() {
+            return Ok(None);
+        }
+        // a relative path from the global config is relative to pwd
+        Ok(Some(Self {
+            dir: None,
+            ..Self::read(&APP_CONFIG_PATH)?
+        }))
+    }
+    /// Reads and parses a config file in any of the supported formats, errors point to the line
+    /// and column at fault where the format allows it
+    pub fn read(file: &Path) -> Result<Self> {
+        let body = fs::read_to_string(file)?;
+        let at = |line: usize, col: usize, msg: &dyn fmt::Display| {
+            // toml and serde_json already end their messages with the position
+            let msg = msg.to_string();
+            let msg = msg.split(" at line ").next().unwrap_or_default();
+            Error::Config(format!("{}:{}:{}: {}", file.display(), line, col, msg))
+        };
+        let mut cfg: Self = match Format::of(file) {
+            Format::Ron => ron::from_str(&body)
+                .map_err(|err| at(err.position.line, err.position.col, &err.code))?,
+            Format::Toml => toml::from_str(&body).map_err(|err| match err.line_col() {
+                Some((line, col)) => at(line + 1, col + 1, &err),
+                None => Error::Config(format!("{}: {}", file.display(), err)),
+            })?,
+            Format::Json => {
+                serde_json::from_str(&body).map_err(|err| at(err.line(), err.column(), &err))?
+            }
+            Format::CargoMetadata => metadata(&body)
+                .ok_or_else(|| {
+                    Error::Config(format!(
+                        "{}: no [package.metadata.yaadv] table",
+                        file.display()
+                    ))
+                })?
+                .try_into()
+                .map_err(|err| {
+                    Error::Config(format!(
+                        "{}: in [package.metadata.yaadv]: {}",
+                        file.display(),
+                        err
+                    ))
+                })?,
+        };
+        cfg.dir = file.parent().map(Path::to_path_buf);
+        Ok(cfg)
+    }
+    /// Writes the config file in the format its name implies, dropping any comments it had
+    pub fn write(&self, file: &Path) -> Result<()> {
+        let body = match Format::of(file) {
+            Format::Ron => ron::ser::to_string_pretty(self, Default::default())
+                .map_err(|err| Error::Config(err.to_string()))?,
+            Format::Toml => {
+                toml::to_string_pretty(self).map_err(|err| Error::Config(err.to_string()))?
+            }
+            Format::Json => {
+                serde_json::to_string_pretty(self).map_err(|err| Error::Config(err.to_string()))?
+            }
+            Format::CargoMetadata => {
+                return Err(Error::Config(format!(
+                    "refusing to rewrite {}, edit its [package.metadata.yaadv] table instead",
+                    file.display()
+                )))
+            }
+        };
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(fs::write(file, format!("{}\n", body.trim_end()))?)
+    }
+    /// Writes a single key to the config file. A RON file is edited in place, keeping its comments
+    /// and the commented out fields of [`TEMPLATE`], while other formats are rewritten with
+    /// [`Config::write`].
+    ///
+    /// Returns whether the file was edited in place.
+    pub fn write_key(&self, file: &Path, key: &str) -> Result<bool> {
+        let body = match Format::of(file) {
+            Format::Ron => fs::read_to_string(file).ok(),
+            _ => None,
+        };
+        let edited = match body {
+            Some(body) => self.edit_ron(&body, key)?,
+            None => None,
+        };
+        match edited {
+            Some(body) => {
+                fs::write(file, body)?;
+                Ok(true)
+            }
+            None => {
+                self.write(file)?;
+                Ok(false)
+            }
+        }
+    }
+    /// Sets the line of `key` in a RON config, uncommenting it if needed, `None` if the file isn't
+    /// laid out one field per line
+    fn edit_ron(&self, body: &str, key: &str) -> Result<Option<String>> {
+        let value = match key {
+            "path" => ron::to_string(&self.path),
+            "base_url" => ron::to_string(&self.base_url),
+            "jobs" => ron::to_string(&self.jobs),
+            "delay" => ron::to_string(&self.delay),
+            "retries" => ron::to_string(&self.retries),
+            "profile" => ron::to_string(&self.profile),
+            _ => return Err(unknown_key(key)),
+        }
+        .map_err(|err| Error::Config(err.to_string()))?;
+
+        // `Some(commented out)` for the lines setting the key
+        let field = |line: &str| {
+            let line = line.trim_start();
+            let (commented, line) = match line.strip_prefix("//") {
+                Some(line) => (true, line.trim_start()),
+                None => (false, line),
+            };
+            let rest = line.strip_prefix(key)?;
+            rest.trim_start().starts_with(':').then_some(commented)
+        };
+        let mut lines: Vec<String> = body.lines().map(str::to_string).collect();
+        let at = lines
+            .iter()
+            .position(|line| field(line) == Some(false))
+            .or_else(|| lines.iter().position(|line| field(line) == Some(true)));
+        match at {
+            Some(at) => {
+                let indent = &lines[at][..lines[at].len() - lines[at].trim_start().len()];
+                lines[at] = format!("{}{}: {},", indent, key, value);
+            }
+            None => match lines.iter().rposition(|line| line.trim() == ")") {
+                Some(end) => lines.insert(end, format!("    {}: {},", key, value)),
+                None => return Ok(None),
+            },
+        }
+        let edited = format!("{}\n", lines.join("\n"));
+
+        // make sure the edit didn't break anything, for eg. a multi-line value
+        match ron::from_str::<Self>(&edited) {
+            Ok(cfg) if cfg.get(key)? == self.get(key)? => Ok(Some(edited)),
+            _ => Ok(None),
+        }
+    }
+    /// Config from the `YAADV_*` env variables, for eg. `YAADV_BASE_URL`
+    pub fn from_env() -> Result<Self> {
+        let mut cfg = Self::default();
+        for key in Self::KEYS {
+            if let Ok(value) = env::var(Self::env_var(key)) {
+                cfg.set(key, &value)?;
+            }
+        }
+        Ok(cfg)
+    }
+    pub fn env_var(key: &str) -> String {
+        format!("{}{}", ENV_PREFIX, key.to_uppercase())
+    }
+    /// Value of the given key, `None` if it isn't set
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(match key {
+            "path" => self.path.clone(),
+            "base_url" => self.base_url.clone(),
+            "jobs" => self.jobs.map(|v| v.to_string()),
+            "delay" => self.delay.map(|v| v.to_string()),
+            "retries" => self.retries.map(|v| v.to_string()),
+            "profile" => self.profile.clone(),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+    /// Sets the given key, parsing the value as needed
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "path" => self.path = Some(value.to_string()),
+            "base_url" => self.base_url = Some(value.to_string()),
+            "jobs" => self.jobs = Some(parse(key, value)?),
+            "delay" => self.delay = Some(parse(key, value)?),
+            "retries" => self.retries = Some(parse(key, value)?),
+            "profile" => self.profile = Some(value.to_string()),
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+    /// Overrides the values with the ones set in `other`
+    pub fn merge(self, other: Self) -> Self {
+        let (path, dir) = match other.path {
+            Some(path) => (Some(path), other.dir),
+            None => (self.path, self.dir),
+        };
+        Self {
+            path,
+            dir,
+            base_url: other.base_url.or(self.base_url),
+            jobs: other.jobs.or(self.jobs),
+            delay: other.delay.or(self.delay),
+            retries: other.retries.or(self.retries),
+            profile: other.profile.or(self.profile),
+        }
+    }
+}
+
+/// `[package.metadata.yaadv]` table of a `Cargo.toml`
+fn metadata(manifest: &str) -> Option<toml::Value> {
+    let mut manifest: toml::Value = toml::from_str(manifest).ok()?;
+    manifest
+        .get_mut("package")?
+        .get_mut("metadata")?
+        .as_table_mut()?
+        .remove("yaadv")
+}
+
+fn has_metadata(manifest: &Path) -> bool {
+    fs::read_to_string(manifest).is_ok_and(|body| metadata(&body).is_some())
+}
+
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| Error::Config(format!("invalid value `{}` for `{}`", value, key)))
+}
+
+fn unknown_key(key: &str) -> Error {
+    Error::Config(format!(
+        "unknown key `{}`, valid keys are: {}",
+        key,
+        Config::KEYS.join(", ")
+    ))
+}
+
+/// Config layer that an effective value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    Default,
+    Global(PathBuf),
+    Project(PathBuf),
+    /// Env variable name
+    Env(String),
+    Cli,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::Global(path) => write!(f, "global config {}", path.display()),
+            Origin::Project(path) => write!(f, "project config {}", path.display()),
+            Origin::Env(var) => write!(f, "env {}", var),
+            Origin::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// Config merged from all its layers, later layers winning: built-in defaults, global config,
+/// project config, `YAADV_*` env variables, then CLI flags.
+#[derive(Debug, Clone)]
+pub struct Layered {
+    pub config: Config,
+    /// Project config file, if one was found
+    pub project: Option<PathBuf>,
+    origins: BTreeMap<&'static str, Origin>,
+}
+
+impl Layered {
+    pub fn load(cli: Config) -> Result<Self> {
+        let project = Config::find()?;
+        let env_vars = Config::from_env()?;
+        let mut layers = vec![(Config::defaults(), Origin::Default)];
+        if let Some(global) = Config::load_global()? {
+            layers.push((global, Origin::Global(APP_CONFIG_PATH.clone())));
+        }
+        if let Some(file) = &project {
+            layers.push((Config::read(file)?, Origin::Project(file.clone())));
+        }
+        layers.push((env_vars, Origin::Env(String::new())));
+        layers.push((cli, Origin::Cli));
+
+        let mut config = Config::default();
+        let mut origins = BTreeMap::new();
+        for (layer, origin) in layers {
+            for key in Config::KEYS {
+                if layer.get(key)?.is_some() {
+                    let origin = match origin {
+                        Origin::Env(_) => Origin::Env(Config::env_var(key)),
+                        _ => origin.clone(),
+                    };
+                    origins.insert(key, origin);
+                }
+            }
+            config = config.merge(layer);
+        }
+
+        Ok(Self {
+            config,
+            project,
+            origins,
+        })
+    }
+    /// Layer that the effective value of the given key came from, `None` if it isn't set
+    pub fn origin(&self, key: &str) -> Option<&Origin> {
+        self.origins.get(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_template_in_place() {
+        let mut cfg: Config = ron::from_str(TEMPLATE).unwrap();
+        cfg.set("jobs", "8").unwrap();
+        let edited = cfg.edit_ron(TEMPLATE, "jobs").unwrap().unwrap();
+        assert!(edited.contains("\n    jobs: Some(8),\n"));
+        assert!(edited.contains("// Number of concurrent downloads."));
+        assert!(edited.contains("// delay: Some(250),"));
+        assert_eq!(ron::from_str::<Config>(&edited).unwrap().jobs, Some(8));
+    }
+
+    #[test]
+    fn adds_missing_keys() {
+        let body = "(\n    jobs: Some(1),\n)\n";
+        let mut cfg: Config = ron::from_str(body).unwrap();
+        cfg.set("delay", "5").unwrap();
+        assert_eq!(
+            cfg.edit_ron(body, "delay").unwrap().as_deref(),
+            Some("(\n    jobs: Some(1),\n    delay: Some(5),\n)\n")
+        );
+        // not one field per line
+        assert_eq!(cfg.edit_ron("(jobs: Some(1))", "delay").unwrap(), None);
     }
 }
@@ -1,19 +1,283 @@
-use crate::defines::APP_SECRETS_PATH;
+use crate::{
+    defines::{APP_SECRETS_PATH, DEFAULT_PROFILE, ENV_PASSPHRASE, ENV_SESSION},
+    Error, Result,
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, fs, path::Path};
 
+/// Session tokens, stored by profile name
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Secrets {
-    pub session_token: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, String>,
+    /// Profile used when none is given, see [`Secrets::resolve_profile`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<String>,
+    /// Token stored before profiles existed, moved into the default profile on load
+    #[serde(rename = "session_token", skip_serializing)]
+    legacy_token: Option<String>,
+    /// The rest of the secrets, when they're stored encrypted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encrypted: Option<Envelope>,
+    /// Passphrase the secrets get encrypted with when stored, if any
+    #[serde(skip)]
+    passphrase: Option<String>,
 }
 
+/// Secrets encrypted with XChaCha20-Poly1305, using a key derived from a passphrase with Argon2id.
+/// All but `kdf` are base64.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Envelope {
+    /// Parameters the key was derived with, so that changing the defaults later doesn't lock out
+    /// existing secrets
+    kdf: Kdf,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Argon2 parameters, the defaults being the ones of the `argon2` crate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Kdf {
+    algorithm: String,
+    version: u32,
+    /// Memory cost in KiB
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        let params = Params::default();
+        Self {
+            algorithm: Algorithm::default().as_str().to_string(),
+            version: Version::default().into(),
+            m_cost: params.m_cost(),
+            t_cost: params.t_cost(),
+            p_cost: params.p_cost(),
+        }
+    }
+}
+
+impl Kdf {
+    fn argon2(&self) -> Result<Argon2<'static>> {
+        let invalid = |err: argon2::Error| {
+            Error::Config(format!("invalid key derivation parameters: {}", err))
+        };
+        let algorithm = self.algorithm.parse().map_err(invalid)?;
+        let version = self.version.try_into().map_err(invalid)?;
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, None).map_err(invalid)?;
+        Ok(Argon2::new(algorithm, version, params))
+    }
+}
+
+/// Passphrase used to unlock the secrets, asked for at most once per run
+static PASSPHRASE: OnceCell<String> = OnceCell::new();
+
 impl Secrets {
-    pub fn load() -> Secrets {
-        confy::load_path(&*APP_SECRETS_PATH).unwrap_or_default()
+    /// Loads the secrets, asking for the passphrase if they're encrypted, see [`passphrase`]
+    pub fn load() -> Result<Secrets> {
+        let mut secrets: Secrets = confy::load_path(&*APP_SECRETS_PATH)?;
+        if let Some(envelope) = secrets.encrypted.take() {
+            let passphrase = passphrase()?;
+            let active = secrets.active.take();
+            secrets = ron::from_str(&envelope.open(passphrase)?)
+                .map_err(|err| Error::Config(format!("invalid decrypted secrets: {}", err)))?;
+            secrets.active = active.or(secrets.active);
+            secrets.passphrase = Some(passphrase.to_string());
+        }
+        if let Some(token) = secrets.legacy_token.take() {
+            secrets
+                .profiles
+                .entry(DEFAULT_PROFILE.to_string())
+                .or_insert(token);
+        }
+        Ok(secrets)
     }
-    pub fn store(self) -> anyhow::Result<()> {
+    /// Stores the secrets, encrypted if they were loaded encrypted or [`Secrets::encrypt`] was used.
+    ///
+    /// The active profile is always kept in plaintext, so that it can be read without unlocking
+    /// the secrets.
+    pub fn store(mut self) -> Result<()> {
+        if let Some(passphrase) = self.passphrase.take() {
+            let active = self.active.take();
+            let plaintext = ron::to_string(&self).map_err(|err| Error::Config(err.to_string()))?;
+            self = Secrets {
+                active,
+                encrypted: Some(Envelope::seal(&plaintext, &passphrase)?),
+                ..Default::default()
+            };
+        }
         confy::store_path(&*APP_SECRETS_PATH, self).map_err(Into::into)
     }
-    pub fn get_session_token(&self) -> Option<&str> {
-        Some(self.session_token.as_ref()?)
+    pub fn is_encrypted(&self) -> bool {
+        self.passphrase.is_some()
+    }
+    /// Encrypts the secrets with the given passphrase from now on
+    pub fn encrypt(&mut self, passphrase: String) {
+        self.passphrase = Some(passphrase);
+    }
+    /// Stores the secrets in plaintext from now on
+    pub fn decrypt(&mut self) {
+        self.passphrase = None;
+    }
+    pub fn get(&self, profile: &str) -> Option<&str> {
+        Some(self.profiles.get(profile)?)
+    }
+    pub fn set(&mut self, profile: &str, token: String) -> Result<()> {
+        check_profile_name(profile)?;
+        self.profiles.insert(profile.to_string(), token);
+        Ok(())
     }
+    /// Removes the profile, returning its token if it existed
+    pub fn remove(&mut self, profile: &str) -> Option<String> {
+        if self.active.as_deref() == Some(profile) {
+            self.active = None;
+        }
+        self.profiles.remove(profile)
+    }
+    /// Profile to use, `profile` if given, otherwise the active profile or `default`.
+    ///
+    /// Never asks for the passphrase, as the active profile is stored in plaintext.
+    pub fn resolve_profile(profile: Option<&str>) -> Result<String> {
+        let profile = match profile {
+            Some(profile) => profile.to_string(),
+            None => {
+                let secrets: Secrets = confy::load_path(&*APP_SECRETS_PATH)?;
+                secrets
+                    .active
+                    .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+            }
+        };
+        check_profile_name(&profile)?;
+        Ok(profile)
+    }
+}
+
+impl Envelope {
+    fn seal(plaintext: &str, passphrase: &str) -> Result<Self> {
+        let mut salt = [0; 16];
+        OsRng.fill_bytes(&mut salt);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let kdf = Kdf::default();
+        let ciphertext = cipher(&kdf, passphrase, &salt)?
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| Error::Config("could not encrypt the secrets".to_string()))?;
+        Ok(Self {
+            kdf,
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+    fn open(&self, passphrase: &str) -> Result<String> {
+        let decode = |field: &str| {
+            BASE64
+                .decode(field)
+                .map_err(|_| Error::Config("malformed encrypted secrets".to_string()))
+        };
+        let nonce = decode(&self.nonce)?;
+        if nonce.len() != 24 {
+            return Err(Error::Config("malformed encrypted secrets".to_string()));
+        }
+        let plaintext = cipher(&self.kdf, passphrase, &decode(&self.salt)?)?
+            .decrypt(XNonce::from_slice(&nonce), &*decode(&self.ciphertext)?)
+            .map_err(|_| Error::WrongPassphrase)?;
+        String::from_utf8(plaintext).map_err(|_| Error::WrongPassphrase)
+    }
+}
+
+fn cipher(kdf: &Kdf, passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305> {
+    let mut key = [0; 32];
+    kdf.argon2()?
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| Error::Config(format!("could not derive the secrets key: {}", err)))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+/// Passphrase for the encrypted secrets, from the `YAADV_PASSPHRASE` env variable, or else
+/// prompted for
+pub fn passphrase() -> Result<&'static str> {
+    PASSPHRASE
+        .get_or_try_init(|| match env::var(ENV_PASSPHRASE) {
+            Ok(passphrase) => Ok(passphrase),
+            Err(_) => inquire::Password::new("Passphrase to unlock your secrets:")
+                .with_display_mode(inquire::PasswordDisplayMode::Masked)
+                .without_confirmation()
+                .prompt()
+                .map_err(|_| {
+                    Error::Config(format!(
+                        "a passphrase is needed to unlock the secrets, set {} to give it non-interactively",
+                        ENV_PASSPHRASE
+                    ))
+                }),
+        })
+        .map(String::as_str)
+}
+
+/// Profile names end up in file paths, so they're kept to a safe set of characters
+fn check_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Config(format!(
+            "invalid profile name `{}`, only letters, digits, `-` and `_` are allowed",
+            name
+        )))
+    }
+}
+
+/// Session token to use for the profile, taken from the first of these that's set:
+/// `token_file`, the `YAADV_SESSION` env variable, then `secrets.ron`
+pub fn session_token(token_file: Option<&Path>, profile: &str) -> Result<Option<String>> {
+    if let Some(token) = given_session_token(token_file)? {
+        return Ok(Some(token));
+    }
+    Ok(Secrets::load()?.get(profile).map(ToString::to_string))
+}
+
+/// Session token given through `token_file` or the `YAADV_SESSION` env variable, without
+/// touching the stored secrets
+pub fn given_session_token(token_file: Option<&Path>) -> Result<Option<String>> {
+    if let Some(file) = token_file {
+        let token = fs::read_to_string(file)?.trim().to_string();
+        if token.is_empty() {
+            return Err(Error::Config(format!(
+                "token file {} is empty",
+                file.display()
+            )));
+        }
+        return Ok(Some(token));
+    }
+    if let Some(token) = env::var(ENV_SESSION)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+    {
+        return Ok(Some(token.trim().to_string()));
+    }
+    Ok(None)
+}
+
+/// Stable, non-reversible key of a session token, see [`crate::account::Accounts`]
+pub fn account_key(session_token: &str) -> String {
+    // FNV-1a, so that the key stays the same across Rust versions
+    let hash = session_token
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{:016x}", hash)
 }
@@ -1,9 +1,23 @@
 use once_cell::sync::Lazy;
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 pub const APP_DIR: &str = "com.github.nozwock.yadv";
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+/// Prefix of the env variables that override config values, for eg. `YAADV_BASE_URL`
+pub const ENV_PREFIX: &str = "YAADV_";
+pub const ENV_SESSION: &str = "YAADV_SESSION";
+pub const ENV_PASSPHRASE: &str = "YAADV_PASSPHRASE";
+pub const DEFAULT_PROFILE: &str = "default";
+pub const DEFAULT_WORKERS: usize = 4;
+pub const DEFAULT_MIN_DELAY: Duration = Duration::from_millis(250);
+pub const DEFAULT_RETRIES: u32 = 3;
+pub const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+/// Upper bound of the delay between two retries
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
 pub static APP_SECRETS_PATH: Lazy<PathBuf> =
     Lazy::new(|| app_config_dir().unwrap_or_default().join("secrets.ron"));
+pub static APP_CONFIG_PATH: Lazy<PathBuf> =
+    Lazy::new(|| app_config_dir().unwrap_or_default().join("config.ron"));
 
 pub static API_HEADER_USER_AGENT: [&str; 2] = [
     "User-Agent",
@@ -14,3 +28,7 @@ pub static API_HEADER_FROM: [&str; 2] = ["From", "magenta-duck.ezffr@slmail.me"]
 pub fn app_config_dir() -> Option<PathBuf> {
     Some(dirs::config_dir()?.join(APP_DIR))
 }
+
+pub fn app_cache_dir() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join(APP_DIR))
+}
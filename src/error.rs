@@ -0,0 +1,132 @@
+use std::{fmt, io};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The puzzle is either not unlocked yet or doesn't exist
+    NotUnlocked,
+    /// Session token is missing, invalid or expired
+    InvalidSession,
+    /// AOC is asking us to slow down
+    RateLimited,
+    /// Input isn't available in the local cache while offline
+    NotCached,
+    /// Response didn't look like anything we know of
+    UnexpectedResponse,
+    /// Any other unexpected HTTP status
+    Status(u16),
+    Network(Box<ureq::Transport>),
+    Io(io::Error),
+    Config(String),
+    /// Malformed or out of bounds day/year range
+    InvalidRange(String),
+    /// Malformed output path pattern
+    InvalidPattern(String),
+    /// Encrypted secrets couldn't be decrypted with the given passphrase
+    WrongPassphrase,
+    /// Session cookie couldn't be read from a browser profile
+    CookieImport(String),
+}
+
+impl Error {
+    /// Whether the request might succeed if retried, for eg. on a 5xx or a timeout
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Status(code) => (500..600).contains(code),
+            Error::Network(err) => matches!(
+                err.kind(),
+                ureq::ErrorKind::Dns
+                    | ureq::ErrorKind::ConnectionFailed
+                    | ureq::ErrorKind::Io
+                    | ureq::ErrorKind::ProxyConnect
+            ),
+            Error::Io(err) => matches!(
+                err.kind(),
+                io::ErrorKind::TimedOut
+                    | io::ErrorKind::WouldBlock
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::UnexpectedEof
+            ),
+            _ => false,
+        }
+    }
+    /// Maps an error response from AOC to the matching variant, looking at the body for known messages
+    fn from_response(code: u16, body: &str) -> Self {
+        if body.contains("Please log in") {
+            return Error::InvalidSession;
+        }
+        match code {
+            // "Please don't repeatedly request this endpoint before it unlocks!"
+            404 => Error::NotUnlocked,
+            400 | 401 | 403 => Error::InvalidSession,
+            429 => Error::RateLimited,
+            _ => Error::Status(code),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotUnlocked => write!(f, "puzzle is either not unlocked yet or doesn't exist"),
+            Error::InvalidSession => write!(f, "session token is either invalid or expired"),
+            Error::RateLimited => write!(f, "rate limited by AOC, please try again later"),
+            Error::NotCached => write!(f, "input is not in the local cache"),
+            Error::UnexpectedResponse => write!(f, "could not make sense of the response from AOC"),
+            Error::Status(code) => write!(f, "unexpected response from AOC: status {}", code),
+            Error::Network(err) => write!(f, "network error: {}", err),
+            Error::Io(err) => write!(f, "io error: {}", err),
+            Error::Config(err) => write!(f, "config error: {}", err),
+            Error::InvalidRange(err) => write!(f, "invalid range: {}", err),
+            Error::InvalidPattern(err) => write!(f, "invalid path pattern: {}", err),
+            Error::WrongPassphrase => {
+                write!(
+                    f,
+                    "could not decrypt the secrets, the passphrase is likely wrong"
+                )
+            }
+            Error::CookieImport(err) => write!(f, "could not import session cookie: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Network(err) => Some(err.as_ref()),
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(err: ureq::Error) -> Self {
+        match err {
+            ureq::Error::Status(code, resp) => {
+                Error::from_response(code, &resp.into_string().unwrap_or_default())
+            }
+            ureq::Error::Transport(err) => Error::Network(Box::new(err)),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<confy::ConfyError> for Error {
+    fn from(err: confy::ConfyError) -> Self {
+        Error::Config(err.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::CookieImport(err.to_string())
+    }
+}
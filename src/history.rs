@@ -0,0 +1,215 @@
+use crate::{answer::Verdict, defines::app_config_dir, Error, Result};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    path::PathBuf,
+    time::Duration,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Submission {
+    pub year: i32,
+    pub day: u32,
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+    /// Unix timestamp of the submission
+    pub time: i64,
+}
+
+/// Reason for refusing to submit an answer, based on earlier verdicts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Refusal {
+    AlreadySolved { answer: String },
+    Repeated { verdict: Verdict },
+    AboveTooHigh { answer: String },
+    BelowTooLow { answer: String },
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Refusal::AlreadySolved { answer } => {
+                write!(f, "This part was already solved with `{}`", answer)
+            }
+            Refusal::Repeated { verdict } => {
+                write!(f, "This answer was already submitted: {}", verdict)
+            }
+            Refusal::AboveTooHigh { answer } => write!(
+                f,
+                "This answer is not below `{}`, which is already known to be too high",
+                answer
+            ),
+            Refusal::BelowTooLow { answer } => write!(
+                f,
+                "This answer is not above `{}`, which is already known to be too low",
+                answer
+            ),
+        }
+    }
+}
+
+/// Submissions made from an account, stored next to the secrets
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct History {
+    pub submissions: Vec<Submission>,
+    /// Unix timestamp until which AOC won't accept another answer
+    #[serde(default)]
+    pub cooldown_until: Option<i64>,
+}
+
+impl History {
+    fn path(account: &str) -> PathBuf {
+        app_config_dir()
+            .unwrap_or_default()
+            .join("history")
+            .join(format!("{}.ron", account))
+    }
+    /// Loads the history of the given account, see [`crate::account::Accounts`]
+    pub fn load(account: &str) -> Result<Self> {
+        let path = Self::path(account);
+        confy::load_path(&path).map_err(|err| Error::Config(format!("{}: {}", path.display(), err)))
+    }
+    pub fn store(&self, account: &str) -> Result<()> {
+        confy::store_path(Self::path(account), self).map_err(Into::into)
+    }
+    /// Locks the history of an account until the returned file is dropped, so that submissions
+    /// from other processes wait for the current one to be recorded
+    pub fn lock(account: &str) -> Result<File> {
+        let path = Self::path(account).with_extension("lock");
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        file.lock_exclusive()?;
+        Ok(file)
+    }
+    pub fn record(&mut self, year: i32, day: u32, part: u8, answer: &str, verdict: Verdict) {
+        self.submissions.push(Submission {
+            year,
+            day,
+            part,
+            answer: answer.to_string(),
+            verdict,
+            time: chrono::Utc::now().timestamp(),
+        });
+    }
+    pub fn set_cooldown(&mut self, cooldown: Duration) {
+        self.cooldown_until = Some(chrono::Utc::now().timestamp() + cooldown.as_secs() as i64);
+    }
+    /// Time left before another answer can be submitted
+    pub fn cooldown_left(&self) -> Option<Duration> {
+        let left = self.cooldown_until? - chrono::Utc::now().timestamp();
+        (left > 0).then(|| Duration::from_secs(left as u64))
+    }
+    /// Checks an answer against the earlier verdicts for the same part, returning why it's
+    /// known to be wrong
+    pub fn check(&self, year: i32, day: u32, part: u8, answer: &str) -> Result<(), Refusal> {
+        let number = answer.parse::<i128>().ok();
+        for sub in self
+            .submissions
+            .iter()
+            .filter(|sub| sub.year == year && sub.day == day && sub.part == part)
+        {
+            let known = sub.answer.parse::<i128>().ok();
+            match sub.verdict {
+                Verdict::Correct => {
+                    return Err(Refusal::AlreadySolved {
+                        answer: sub.answer.clone(),
+                    })
+                }
+                Verdict::TooHigh | Verdict::TooLow | Verdict::Wrong if sub.answer == answer => {
+                    return Err(Refusal::Repeated {
+                        verdict: sub.verdict,
+                    })
+                }
+                Verdict::TooHigh if matches!((number, known), (Some(n), Some(k)) if n >= k) => {
+                    return Err(Refusal::AboveTooHigh {
+                        answer: sub.answer.clone(),
+                    })
+                }
+                Verdict::TooLow if matches!((number, known), (Some(n), Some(k)) if n <= k) => {
+                    return Err(Refusal::BelowTooLow {
+                        answer: sub.answer.clone(),
+                    })
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(submissions: &[(&str, Verdict)]) -> History {
+        let mut history = History::default();
+        for (answer, verdict) in submissions {
+            history.record(2022, 1, 1, answer, *verdict);
+        }
+        history
+    }
+
+    #[test]
+    fn check_allows_new_answers() {
+        let history = history(&[("100", Verdict::TooHigh), ("10", Verdict::TooLow)]);
+        assert_eq!(history.check(2022, 1, 1, "50"), Ok(()));
+        // other parts and days are unaffected
+        assert_eq!(history.check(2022, 1, 2, "100"), Ok(()));
+        assert_eq!(history.check(2022, 2, 1, "100"), Ok(()));
+    }
+
+    #[test]
+    fn check_refuses_solved_part() {
+        let history = history(&[("42", Verdict::Correct)]);
+        assert_eq!(
+            history.check(2022, 1, 1, "43"),
+            Err(Refusal::AlreadySolved {
+                answer: "42".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn check_refuses_repeated_answer() {
+        let history = history(&[("abc", Verdict::Wrong)]);
+        assert_eq!(
+            history.check(2022, 1, 1, "abc"),
+            Err(Refusal::Repeated {
+                verdict: Verdict::Wrong
+            })
+        );
+        assert_eq!(history.check(2022, 1, 1, "abd"), Ok(()));
+    }
+
+    #[test]
+    fn check_refuses_out_of_known_bounds() {
+        let history = history(&[("100", Verdict::TooHigh), ("10", Verdict::TooLow)]);
+        assert_eq!(
+            history.check(2022, 1, 1, "150"),
+            Err(Refusal::AboveTooHigh {
+                answer: "100".to_string()
+            })
+        );
+        assert_eq!(
+            history.check(2022, 1, 1, "10"),
+            Err(Refusal::Repeated {
+                verdict: Verdict::TooLow
+            })
+        );
+        assert_eq!(
+            history.check(2022, 1, 1, "5"),
+            Err(Refusal::BelowTooLow {
+                answer: "10".to_string()
+            })
+        );
+    }
+}
@@ -1,10 +1,41 @@
-use std::path::PathBuf;
+use crate::{defines::DEFAULT_PROFILE, template, utils::write_atomic, Error, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Kind of file stored for a puzzle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Kind {
+    #[default]
+    Input,
+    /// Puzzle statement, as Markdown
+    Problem,
+    /// Nth example input of the puzzle statement, starting at 1
+    Example(usize),
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Input => "input",
+            Kind::Problem => "problem",
+            Kind::Example(_) => "example",
+        }
+    }
+}
 
 pub struct AdvInput<'a> {
     pub day: u32,
     pub year: i32,
+    pub kind: Kind,
+    pub part: Option<u8>,
+    /// Profile name the file belongs to
+    pub user: &'a str,
     /// Path to store the input files in
     pub formatted_path: Option<&'a str>,
+    /// Folder that a relative formatted path is relative to, pwd by default
+    pub base_dir: Option<&'a Path>,
 }
 
 impl<'a> AdvInput<'a> {
@@ -12,36 +43,98 @@ impl<'a> AdvInput<'a> {
         Self {
             day,
             year,
+            kind: Kind::default(),
+            part: None,
+            user: DEFAULT_PROFILE,
             formatted_path: None,
+            base_dir: None,
         }
     }
+    pub fn with_kind(mut self, kind: Kind) -> Self {
+        self.kind = kind;
+        self
+    }
+    pub fn with_part(mut self, part: u8) -> Self {
+        self.part = Some(part);
+        self
+    }
+    pub fn with_user(mut self, user: &'a str) -> Self {
+        self.user = user;
+        self
+    }
     pub fn with_formatted_path(mut self, pattern: Option<&'a str>) -> Self {
         self.formatted_path = pattern;
         self
     }
-    fn filename(&self) -> String {
-        format!("day{}.input", self.day)
+    pub fn with_base_dir(mut self, dir: Option<&'a Path>) -> Self {
+        self.base_dir = dir;
+        self
+    }
+    /// Whether the input file already exists and looks complete
+    pub fn is_downloaded(&self) -> bool {
+        // AOC inputs always end with a newline, anything else was likely left by an interrupted write
+        let Ok(path) = self.path() else {
+            return false;
+        };
+        fs::read(path).is_ok_and(|input| input.ends_with(b"\n"))
+    }
+    /// Writes the input file, creating any missing parent folders
+    pub fn write(&self, input: &str) -> Result<()> {
+        write_atomic(&self.path()?, input)
     }
-    pub fn path(&self) -> PathBuf {
-        match self.eval_path() {
-            Some(path) => PathBuf::from(path),
+    /// Errors if the formatted path pattern is invalid, see [`template::render`]
+    pub fn path(&self) -> Result<PathBuf> {
+        Ok(match self.eval_path()? {
+            Some(path) => match self.base_dir {
+                Some(dir) => dir.join(path),
+                None => PathBuf::from(path),
+            },
             // default condition
-            None => PathBuf::from("./inputs")
-                .join(self.year.to_string())
-                .join(self.filename()),
-        }
+            None => match self.kind {
+                // inputs differ by user, so other profiles get their own folder
+                Kind::Input if self.user != DEFAULT_PROFILE => PathBuf::from("./inputs")
+                    .join(self.user)
+                    .join(self.year.to_string())
+                    .join(format!("day{}.input", self.day)),
+                Kind::Input => PathBuf::from("./inputs")
+                    .join(self.year.to_string())
+                    .join(format!("day{}.input", self.day)),
+                Kind::Example(n) => PathBuf::from("./inputs")
+                    .join(self.year.to_string())
+                    .join(format!("day{}.example{}", self.day, n)),
+                Kind::Problem => PathBuf::from("./puzzles")
+                    .join(self.year.to_string())
+                    .join(format!("day{}", self.day))
+                    .join("README.md"),
+            },
+        })
     }
-    pub fn request_url(&self) -> String {
-        format!(
-            "https://adventofcode.com/{}/day/{}/input",
-            self.year, self.day
-        )
-    }
-    fn eval_path(&self) -> Option<String> {
-        Some(
-            self.formatted_path?
-                .replace("{{day}}", &self.day.to_string())
-                .replace("{{year}}", &self.year.to_string()),
-        )
+    fn eval_path(&self) -> Result<Option<String>> {
+        let Some(pattern) = self.formatted_path else {
+            return Ok(None);
+        };
+        // otherwise inputs of different accounts would overwrite, or be mistaken for, each other
+        if self.kind == Kind::Input
+            && self.user != DEFAULT_PROFILE
+            && !template::has_token(pattern, "user")
+        {
+            return Err(Error::InvalidPattern(format!(
+                "`{}` is missing `{{{{user}}}}`, which is needed to keep the inputs of profile `{}` apart",
+                pattern, self.user
+            )));
+        }
+        template::render(pattern, |token| match token {
+            "day" => Some(self.day.to_string()),
+            "year" => Some(self.year.to_string()),
+            "part" => self.part.map(|part| part.to_string()),
+            "kind" => Some(self.kind.name().to_string()),
+            "user" => Some(self.user.to_string()),
+            "n" => match self.kind {
+                Kind::Example(n) => Some(n.to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(Some)
     }
 }
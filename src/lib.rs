@@ -1,6 +1,20 @@
+pub mod account;
+pub mod answer;
 pub mod api;
 pub mod args;
+pub mod browser;
+pub mod cache;
+pub mod calendar;
 pub mod config;
 pub mod credentials;
 pub mod defines;
+pub mod error;
+pub mod history;
 pub mod inputs;
+pub mod problem;
+pub mod range;
+pub mod template;
+pub mod user;
+pub mod utils;
+
+pub use error::{Error, Result};
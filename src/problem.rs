@@ -0,0 +1,351 @@
+use colored::Colorize;
+use scraper::{ElementRef, Html, Node, Selector};
+
+/// Puzzle statement, made of the `article.day-desc` sections of a puzzle page.
+///
+/// Part two is only included once it's unlocked for the session the page was fetched with.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    articles: Vec<String>,
+    base_url: String,
+}
+
+/// Example input found in a puzzle statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    /// Part of the statement the example was found in
+    pub part: u8,
+    pub input: String,
+    /// Expected answers for this example as `(part, answer)`, where they could be found
+    pub answers: Vec<(u8, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Markdown,
+    Text,
+}
+
+impl Problem {
+    /// `base_url` is used to turn relative links into absolute ones
+    pub fn parse(html: &str, base_url: &str) -> Self {
+        let selector = Selector::parse("article.day-desc").unwrap();
+        Self {
+            articles: Html::parse_document(html)
+                .select(&selector)
+                .map(|article| article.html())
+                .collect(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+    /// Number of parts available in the statement
+    pub fn parts(&self) -> usize {
+        self.articles.len()
+    }
+    /// Extracts the `<pre><code>` blocks right after a paragraph mentioning an example.
+    ///
+    /// The expected answer of a part is taken from the last `<code><em>` of its section, and
+    /// given to the first example of that section, or to the very first example if the section
+    /// has none of its own.
+    pub fn examples(&self) -> Vec<Example> {
+        let pre = Selector::parse("pre").unwrap();
+        let code_em = Selector::parse("code > em").unwrap();
+        let mut out: Vec<Example> = vec![];
+        for (idx, article) in self.articles.iter().enumerate() {
+            let article = Html::parse_fragment(article);
+            let first = out.len();
+            for pre in article.select(&pre) {
+                let prev = pre.prev_siblings().find_map(ElementRef::wrap);
+                let after_example = prev.is_some_and(|prev| {
+                    let text: String = prev.text().collect();
+                    prev.value().name() == "p" && text.to_lowercase().contains("example")
+                });
+                if after_example {
+                    out.push(Example {
+                        part: idx as u8 + 1,
+                        input: pre.text().collect(),
+                        answers: vec![],
+                    });
+                }
+            }
+            let answer = article
+                .select(&code_em)
+                .last()
+                .map(|em| em.text().collect::<String>());
+            let example = if first < out.len() { first } else { 0 };
+            if let (Some(answer), Some(example)) = (answer, out.get_mut(example)) {
+                example.answers.push((idx as u8 + 1, answer));
+            }
+        }
+        out
+    }
+    pub fn to_markdown(&self) -> String {
+        self.render(Format::Markdown)
+    }
+    /// Renders as styled text, meant for the terminal
+    pub fn to_text(&self) -> String {
+        self.render(Format::Text)
+    }
+    fn render(&self, format: Format) -> String {
+        self.articles
+            .iter()
+            .map(|article| {
+                let mut renderer = Renderer {
+                    format,
+                    base_url: &self.base_url,
+                    out: String::new(),
+                };
+                renderer.block(Html::parse_fragment(article).root_element());
+                renderer.out.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+struct Renderer<'a> {
+    format: Format,
+    base_url: &'a str,
+    out: String,
+}
+
+impl Renderer<'_> {
+    fn push_block(&mut self, block: &str) {
+        self.out.push_str(block);
+        self.out.push_str("\n\n");
+    }
+    fn block(&mut self, el: ElementRef) {
+        match el.value().name() {
+            "h2" => {
+                let title = self.inline(el, false);
+                match self.format {
+                    Format::Markdown => self.push_block(&format!("## {}", title)),
+                    Format::Text => self.push_block(&title.bold().bright_white().to_string()),
+                }
+            }
+            "p" => {
+                let text = self.inline(el, false);
+                self.push_block(&text)
+            }
+            "pre" => match self.format {
+                Format::Markdown => {
+                    let code: String = el.text().collect();
+                    self.push_block(&format!("```\n{}\n```", code.trim_end_matches('\n')))
+                }
+                Format::Text => {
+                    let code = self.inline(el, true);
+                    let code = code
+                        .trim_end_matches('\n')
+                        .lines()
+                        .map(|line| format!("    {}", line))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    self.push_block(&code)
+                }
+            },
+            "ul" => {
+                let items = el
+                    .children()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|li| li.value().name() == "li")
+                    .map(|li| format!("- {}", self.inline(li, false)))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.push_block(&items)
+            }
+            _ => el
+                .children()
+                .filter_map(ElementRef::wrap)
+                .for_each(|child| self.block(child)),
+        }
+    }
+    /// Renders the children of `el` as a single run of text.
+    ///
+    /// `raw` keeps whitespace as is and skips escaping, for code.
+    fn inline(&self, el: ElementRef, raw: bool) -> String {
+        let mut out = String::new();
+        for child in el.children() {
+            match child.value() {
+                Node::Text(text) if raw => out.push_str(text),
+                Node::Text(text) => out.push_str(&self.escape(&collapse_whitespace(text))),
+                Node::Element(_) => {
+                    let child = ElementRef::wrap(child).unwrap();
+                    out.push_str(&self.inline_element(child, raw))
+                }
+                _ => (),
+            }
+        }
+        out
+    }
+    fn inline_element(&self, el: ElementRef, raw: bool) -> String {
+        let class = el.value().attr("class").unwrap_or_default();
+        match (self.format, el.value().name()) {
+            (_, "br") => "\n".to_string(),
+            (Format::Markdown, "em") if raw => self.inline(el, raw),
+            (Format::Markdown, "em") => format!("**{}**", self.inline(el, raw)),
+            (Format::Text, "em") if class == "star" => self.inline(el, raw).yellow().to_string(),
+            (Format::Text, "em") => self.inline(el, raw).bold().bright_white().to_string(),
+            (Format::Markdown, "code") => {
+                let code: String = el.text().collect();
+                let has_em = el
+                    .children()
+                    .filter_map(ElementRef::wrap)
+                    .any(|child| child.value().name() == "em");
+                if has_em {
+                    format!("**`{}`**", code)
+                } else {
+                    format!("`{}`", code)
+                }
+            }
+            (Format::Text, "code") => self.inline(el, true).green().to_string(),
+            (Format::Markdown, "a") => match el.value().attr("href") {
+                Some(href) => format!("[{}]({})", self.inline(el, raw), self.absolute(href)),
+                None => self.inline(el, raw),
+            },
+            (Format::Text, "a") => self.inline(el, raw).underline().to_string(),
+            _ => self.inline(el, raw),
+        }
+    }
+    fn escape(&self, text: &str) -> String {
+        match self.format {
+            Format::Markdown => text
+                .replace('\\', "\\\\")
+                .replace('*', "\\*")
+                .replace('_', "\\_")
+                .replace('`', "\\`"),
+            Format::Text => text.to_string(),
+        }
+    }
+    fn absolute(&self, href: &str) -> String {
+        if href.starts_with('/') {
+            format!("{}{}", self.base_url, href)
+        } else {
+            href.to_string()
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last_ws = false;
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            if !last_ws {
+                out.push(' ');
+            }
+            last_ws = true;
+        } else {
+            out.push(ch);
+            last_ws = false;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(articles: &[&str]) -> Problem {
+        let articles: String = articles
+            .iter()
+            .map(|article| format!("<article class=\"day-desc\">{}</article>", article))
+            .collect();
+        Problem::parse(
+            &format!("<html><body><main>{}</main></body></html>", articles),
+            "https://adventofcode.com",
+        )
+    }
+
+    const PART_ONE: &str = "<h2>--- Day 1: Test ---</h2>\
+        <p>Some intro, with <code>code</code> in it.</p>\
+        <pre><code>not a sample</code></pre>\
+        <p>For example:</p>\
+        <pre><code>1\n2\n</code></pre>\
+        <p>In this example, the answer is <code><em>3</em></code>.</p>";
+
+    #[test]
+    fn finds_example_and_answer() {
+        let examples = page(&[PART_ONE]).examples();
+        assert_eq!(
+            examples,
+            [Example {
+                part: 1,
+                input: "1\n2\n".to_string(),
+                answers: vec![(1, "3".to_string())],
+            }]
+        );
+    }
+
+    #[test]
+    fn gives_part_two_answer_to_first_example() {
+        let part_two = "<h2>--- Part Two ---</h2>\
+            <p>Now the answer is <code><em>6</em></code> instead.</p>";
+        let examples = page(&[PART_ONE, part_two]).examples();
+        assert_eq!(examples.len(), 1);
+        assert_eq!(
+            examples[0].answers,
+            [(1, "3".to_string()), (2, "6".to_string())]
+        );
+    }
+
+    #[test]
+    fn finds_several_examples() {
+        let part_one = "<p>Here's an example:</p><pre><code>a</code></pre>\
+            <p>Another example:</p><pre><code>b</code></pre>\
+            <p>So the answer is <code><em>2</em></code>.</p>";
+        let part_two = "<p>A larger example:</p><pre><code>c</code></pre>\
+            <p>Which gives <code><em>4</em></code>.</p>";
+        let examples = page(&[part_one, part_two]).examples();
+        let summary: Vec<_> = examples
+            .iter()
+            .map(|example| {
+                (
+                    example.part,
+                    example.input.as_str(),
+                    example.answers.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1, "a", vec![(1, "2".to_string())]),
+                (1, "b", vec![]),
+                (2, "c", vec![(2, "4".to_string())]),
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_markdown() {
+        let problem = page(&["<h2>--- Day 1: Test ---</h2>\
+             <p>Multiply *all* the_numbers, see <a href=\"/2022/about\">about</a> and \
+             <a href=\"https://example.com\">this</a>.</p>\
+             <p>The answer is <code><em>42</em></code>, not <code>41</code> or <em>40</em>.</p>\
+             <pre><code>1 * 2\n<em>3</em>\n</code></pre>\
+             <ul><li>one</li><li>two</li></ul>"]);
+        assert_eq!(
+            problem.to_markdown(),
+            "## --- Day 1: Test ---\n\n\
+             Multiply \\*all\\* the\\_numbers, see [about](https://adventofcode.com/2022/about) \
+             and [this](https://example.com).\n\n\
+             The answer is **`42`**, not `41` or **40**.\n\n\
+             ```\n1 * 2\n3\n```\n\n\
+             - one\n- two"
+        );
+    }
+
+    #[test]
+    fn renders_text() {
+        colored::control::set_override(false);
+        let problem = page(&[
+            "<h2>--- Day 1 ---</h2><p>Plain *text*, <em>no</em> escaping.</p>\
+             <pre><code>a\nb\n</code></pre>",
+        ]);
+        assert_eq!(
+            problem.to_text(),
+            "--- Day 1 ---\n\nPlain *text*, no escaping.\n\n    a\n    b"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{Error, Result};
+use std::str::FromStr;
+
+/// Comma separated list of inclusive ranges, for eg. `1-5,9,12-` or `all`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeList(Vec<(Option<u32>, Option<u32>)>);
+
+impl RangeList {
+    /// Range list covering everything within the bounds it gets resolved with
+    pub fn all() -> Self {
+        Self(vec![(None, None)])
+    }
+    /// Resolves the list into sorted and deduplicated values within `min..=max`, where open ends
+    /// of a range get replaced by the bounds.
+    ///
+    /// Any explicitly given value out of the bounds is an error.
+    pub fn resolve(&self, min: u32, max: u32) -> Result<Vec<u32>> {
+        let mut out = vec![];
+        for &(start, end) in &self.0 {
+            for value in [start, end].into_iter().flatten() {
+                if !(min..=max).contains(&value) {
+                    return Err(Error::InvalidRange(format!(
+                        "{} is out of the valid range {}-{}",
+                        value, min, max
+                    )));
+                }
+            }
+            out.extend(start.unwrap_or(min)..=end.unwrap_or(max));
+        }
+        out.sort_unstable();
+        out.dedup();
+        Ok(out)
+    }
+}
+
+impl FromStr for RangeList {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.trim().eq_ignore_ascii_case("all") {
+            return Ok(Self::all());
+        }
+        let parse = |value: &str| -> Result<Option<u32>> {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            value
+                .parse()
+                .map(Some)
+                .map_err(|_| Error::InvalidRange(format!("`{}` is not a valid number", value)))
+        };
+        s.split(',')
+            .map(|part| {
+                let (start, end) = match part.split_once('-') {
+                    Some((start, end)) => (parse(start)?, parse(end)?),
+                    None => {
+                        let value = parse(part)?
+                            .ok_or_else(|| Error::InvalidRange(format!("empty item in `{}`", s)))?;
+                        (Some(value), Some(value))
+                    }
+                };
+                match (start, end) {
+                    (Some(start), Some(end)) if start > end => Err(Error::InvalidRange(format!(
+                        "`{}` has its start after its end",
+                        part.trim()
+                    ))),
+                    _ => Ok((start, end)),
+                }
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(list: &str, min: u32, max: u32) -> Result<Vec<u32>> {
+        list.parse::<RangeList>()?.resolve(min, max)
+    }
+
+    #[test]
+    fn resolves_ranges_and_values() {
+        assert_eq!(
+            resolve("1-5,9,12-", 1, 14).unwrap(),
+            [1, 2, 3, 4, 5, 9, 12, 13, 14]
+        );
+        assert_eq!(resolve("-3", 1, 25).unwrap(), [1, 2, 3]);
+        assert_eq!(resolve(" 3 - 4 , 2 ", 1, 25).unwrap(), [2, 3, 4]);
+    }
+
+    #[test]
+    fn resolves_all() {
+        assert_eq!("all".parse::<RangeList>().unwrap(), RangeList::all());
+        assert_eq!(resolve("ALL", 1, 3).unwrap(), [1, 2, 3]);
+        assert_eq!(resolve("-", 1, 3).unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn sorts_and_dedups() {
+        assert_eq!(resolve("5,1-3,2", 1, 25).unwrap(), [1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn rejects_reversed_ranges() {
+        assert!(matches!(
+            "5-3".parse::<RangeList>(),
+            Err(Error::InvalidRange(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_out_of_bounds() {
+        assert!(matches!(resolve("0-3", 1, 25), Err(Error::InvalidRange(_))));
+        assert!(matches!(resolve("26", 1, 25), Err(Error::InvalidRange(_))));
+        assert!(matches!(
+            resolve("20-30", 1, 25),
+            Err(Error::InvalidRange(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        for list in ["", "1,,2", "a", "1-b", "-1-"] {
+            assert!(
+                matches!(list.parse::<RangeList>(), Err(Error::InvalidRange(_))),
+                "{}",
+                list
+            );
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{Error, Result};
+use std::env;
+
+/// Renders a path pattern, substituting every `{{token}}` with its value from `lookup`.
+///
+/// Tokens can take a width to pad to, with a leading `0` for zero padding, for eg. `{{day:02}}`.
+/// `{{env:VAR}}` is substituted with the `VAR` env variable.
+///
+/// Unknown tokens, missing values or malformed tokens are an error.
+pub fn render(pattern: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| invalid(pattern, "unclosed `{{`"))?;
+        let token = &rest[start + 2..start + end];
+        out.push_str(&eval(pattern, token, &lookup)?);
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Whether the pattern uses the given token, with or without a format
+pub fn has_token(pattern: &str, name: &str) -> bool {
+    let mut rest = pattern;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            return false;
+        };
+        let token = rest[start + 2..start + end].trim();
+        if token.split(':').next() == Some(name) {
+            return true;
+        }
+        rest = &rest[start + end + 2..];
+    }
+    false
+}
+
+fn eval(pattern: &str, token: &str, lookup: &impl Fn(&str) -> Option<String>) -> Result<String> {
+    let token = token.trim();
+    if let Some(var) = token.strip_prefix("env:") {
+        return env::var(var)
+            .map_err(|_| invalid(pattern, &format!("env variable `{}` is not set", var)));
+    }
+
+    let (name, spec) = match token.split_once(':') {
+        Some((name, spec)) => (name, Some(spec)),
+        None => (token, None),
+    };
+    let value = lookup(name)
+        .ok_or_else(|| invalid(pattern, &format!("unknown or unavailable token `{}`", name)))?;
+    match spec {
+        None => Ok(value),
+        Some(spec) => {
+            let width: usize = spec
+                .parse()
+                .map_err(|_| invalid(pattern, &format!("invalid format `{}`", spec)))?;
+            if spec.starts_with('0') {
+                Ok(format!("{:0>width$}", value, width = width))
+            } else {
+                Ok(format!("{:>width$}", value, width = width))
+            }
+        }
+    }
+}
+
+fn invalid(pattern: &str, reason: &str) -> Error {
+    Error::InvalidPattern(format!("{} in `{}`", reason, pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(token: &str) -> Option<String> {
+        match token {
+            "day" => Some("7".to_string()),
+            "year" => Some("2022".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn renders_tokens() {
+        assert_eq!(
+            render("./{{year}}/day{{ day }}.txt", lookup).unwrap(),
+            "./2022/day7.txt"
+        );
+        assert_eq!(render("no tokens", lookup).unwrap(), "no tokens");
+    }
+
+    #[test]
+    fn pads_tokens() {
+        assert_eq!(render("day{{day:02}}", lookup).unwrap(), "day07");
+        assert_eq!(render("day{{day:3}}", lookup).unwrap(), "day  7");
+        assert_eq!(render("{{year:02}}", lookup).unwrap(), "2022");
+    }
+
+    #[test]
+    fn rejects_invalid_tokens() {
+        for pattern in [
+            "{{month}}",
+            "day{{day",
+            "{{day:x}}",
+            "{{env:YAADV_TEMPLATE_TEST_UNSET}}",
+        ] {
+            assert!(
+                matches!(render(pattern, lookup), Err(Error::InvalidPattern(_))),
+                "{}",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn finds_tokens() {
+        assert!(has_token("{{user}}/day{{day}}", "user"));
+        assert!(has_token("ex{{n:02}}", "n"));
+        assert!(!has_token("{{env:user}}/{{day}}", "user"));
+        assert!(!has_token("user/{{n", "n"));
+    }
+}
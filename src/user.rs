@@ -0,0 +1,47 @@
+use scraper::{Html, Node, Selector};
+
+/// AOC account a session token belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    /// Display name, as shown in the page header
+    pub name: String,
+    /// AOC user id, where the page shows it
+    pub id: Option<u64>,
+}
+
+impl User {
+    /// Parses the logged in user out of an AOC page, `None` if the page isn't logged in
+    pub fn parse(html: &str) -> Option<Self> {
+        let selector = Selector::parse("div.user").unwrap();
+        let html = Html::parse_document(html);
+        let user = html.select(&selector).next()?;
+        // skip the star count and any other child elements, only the name is a direct text node
+        let name: String = user
+            .children()
+            .filter_map(|child| match child.value() {
+                Node::Text(text) => Some(&**text),
+                _ => None,
+            })
+            .collect();
+
+        let body = Selector::parse("body").unwrap();
+        let text: String = html
+            .select(&body)
+            .next()
+            .map(|body| body.text().collect())
+            .unwrap_or_default();
+        Some(Self {
+            name: name.trim().to_string(),
+            id: parse_id(&text),
+        })
+    }
+}
+
+/// Parses the id out of "(anonymous user #123456)"
+fn parse_id(text: &str) -> Option<u64> {
+    let (_, rest) = text.split_once("anonymous user #")?;
+    let end = rest
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
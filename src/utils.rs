@@ -0,0 +1,14 @@
+use crate::Result;
+use std::{fs, path::Path};
+
+/// Writes the file through a temp file next to it, so that an interrupted write never leaves it
+/// truncated. Missing parent folders are created.
+pub fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    Ok(fs::rename(tmp, path)?)
+}